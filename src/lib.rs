@@ -2,7 +2,16 @@
 //!
 //!## Loggers
 //!
-//!- [RFC 3164](https://datatracker.ietf.org/doc/html/rfc3164) - Logger is limited to buffer of 1024 bytes and splits records into chunks with common header
+//!- [RFC 3164](https://datatracker.ietf.org/doc/html/rfc3164) - Logger is limited to buffer of 1024 bytes by default and splits or truncates records exceeding it
+//!- [RFC 5424](https://datatracker.ietf.org/doc/html/rfc5424) - Logger is limited to buffer of 2048 bytes by default and splits or truncates records exceeding it
+//!
+//!Both loggers can be converted into `Sync` variant via `shared()`, when `std` feature is enabled
+//!
+//!Splitting of records into chunks is configured via `split` module (e.g. to split on whitespace and add continuation markers)
+//!
+//!Control characters within records can be escaped or replaced via `sanitize` module, preventing injection of fake records
+//!
+//!Records of both formats can be read back using `syslog::parser`
 //!
 //!## Features
 //!
@@ -10,6 +19,9 @@
 //!- `log04` - Enables integration with `log` 0.4
 //!- `tracing` - Enables integration with latest version of `tracing`
 //!- `tracing-full` - Enables capture span content to be printed together with events. Implies `tracing` and `std`.
+//!- `tls` - Enables TLS transport using `rustls`. Implies `std`.
+//!- `tokio` - Enables async transports and loggers using `tokio`. Implies `std`.
+//!- `testing` - Enables local syslog server and scripted mock transport for integration tests. Implies `std`.
 
 #![no_std]
 #![warn(missing_docs)]
@@ -18,6 +30,9 @@
 
 use core::fmt;
 
+#[cfg(feature = "std")]
+extern crate std;
+
 #[doc(hidden)]
 #[cfg(not(debug_assertions))]
 macro_rules! unreach {
@@ -40,52 +55,47 @@ pub mod syslog;
 pub use syslog::{Facility, Severity};
 pub mod writer;
 use writer::Writer;
+pub mod split;
+pub mod sanitize;
+#[cfg(feature = "testing")]
+pub mod testing;
 #[cfg(feature = "log04")]
 pub mod log04;
 #[cfg(feature = "tracing")]
 pub mod tracing;
 
-///Buffer type to hold max possible message as per RFC 3164 (1024 bytes)
-pub type Rfc3164Buffer = str_buf::StrBuf<{ str_buf::capacity(1024) }>;
+///Re-export of buffer crate, so that custom buffer size can be specified (e.g. `str_buf::capacity(8192)`)
+pub use str_buf;
 
-///RFC 3164 record writer.
+///Syslog record writer.
 ///
 ///It can be used to efficiently create logging record via `fmt::Write` interface
 ///
-///When necessary record will be split into chunks of 1024 bytes
+///Header of record is written on creation, so writer is the same for both formats.
+///
+///When necessary record will be split into chunks of up to buffer capacity or `Syslog::with_max_size`, each including header, as configured by `Syslog::with_split`
 ///
 ///On Drop internal buffer is cleared
-pub struct Rfc3164RecordWriter<'a, W: writer::MakeTransport> {
+pub struct RecordWriter<'a, W: writer::MakeTransport, const N: usize> {
     writer: &'a mut Writer<W>,
-    buffer: &'a mut Rfc3164Buffer,
+    buffer: &'a mut str_buf::StrBuf<N>,
     severity: Severity,
-    header_size: usize,
+    chunker: split::Chunker,
     retry_count: u8,
+    retry_policy: &'a writer::RetryPolicy,
 }
 
-impl<'a, W: writer::MakeTransport> Rfc3164RecordWriter<'a, W> {
+impl<'a, W: writer::MakeTransport, const N: usize> RecordWriter<'a, W, N> {
     #[inline]
-    ///Creates new record writer
-    fn new(syslog: &'a Syslog, writer: &'a mut Writer<W>, buffer: &'a mut Rfc3164Buffer, severity: Severity) -> Self {
-        let timestamp = syslog::header::Timestamp::now_utc();
-        let header = syslog::header::Rfc3164 {
-            pri: severity.priority(syslog.facility),
-            hostname: &syslog.hostname,
-            tag: &syslog.tag,
-            pid: os_id::process::get_raw_id() as _,
-            timestamp,
-        };
-
-        header.write_buffer(buffer);
-        buffer.push_str(" ");
-        let header_size = buffer.len();
-
-        Rfc3164RecordWriter {
+    ///Creates new record writer with header of `header_size` already written into `buffer`
+    fn new(syslog: &'a Syslog, writer: &'a mut Writer<W>, buffer: &'a mut str_buf::StrBuf<N>, severity: Severity, header_size: usize) -> Self {
+        RecordWriter {
             writer,
             buffer,
             severity,
-            header_size,
+            chunker: split::Chunker::new(syslog, header_size, str_buf::StrBuf::<N>::capacity()),
             retry_count: syslog.retry_count,
+            retry_policy: &syslog.retry_policy,
         }
     }
 
@@ -95,38 +105,37 @@ impl<'a, W: writer::MakeTransport> Rfc3164RecordWriter<'a, W> {
     ///
     ///On success, text will be fully written
     pub fn write_str(&mut self, mut text: &str) -> Result<(), W::Error> {
-        loop {
-            if text.is_empty() {
-                break Ok(())
-            }
-
-            let consumed = self.buffer.push_str(text);
+        //Everything that fits is kept in buffer, user has to manually flush once he is ready
+        self.send_chunks(&mut text, false)
+    }
 
-            if consumed < text.len() {
-                self.flush()?;
-                text = &text[consumed..];
-                continue;
-            } else {
-                //Everything consumed, so carry on.
-                //User has to manually flush once he is ready
-                break Ok(());
-            }
-        }
+    #[inline]
+    ///Writes whole `text` as single record, so that total number of parts is known
+    fn write_text(&mut self, text: &str) -> Result<(), W::Error> {
+        self.chunker.start(self.buffer, text);
+        self.write_str(text)?;
+        self.flush()
     }
 
     #[inline(always)]
     ///Clears current content of the record, preparing it for next write
     pub fn clear(&mut self) {
+        self.chunker.reset();
         //This is safe because we know exact header size written
         unsafe {
-            self.buffer.set_len(self.header_size);
+            self.buffer.set_len(self.chunker.header_size());
         }
     }
 
-    #[inline(always)]
-    fn flush_without_clear(&mut self) -> Result<(), W::Error> {
-        if self.buffer.len() > self.header_size {
-            self.writer.write_buffer(self.buffer.as_str(), self.severity, self.retry_count)?;
+    //Sends chunks until `text` fits buffer, or until buffer is empty when `is_flush`
+    fn send_chunks(&mut self, text: &mut &str, is_flush: bool) -> Result<(), W::Error> {
+        while let Some(cut) = self.chunker.next_chunk(self.buffer, text, is_flush) {
+            let chunk = self.chunker.chunk(self.buffer, cut);
+            if let Err(error) = self.writer.write_buffer_with(chunk, self.severity, self.retry_count, self.retry_policy) {
+                self.chunker.revert(self.buffer, cut);
+                return Err(error);
+            }
+            self.chunker.commit(self.buffer, cut);
         }
         Ok(())
     }
@@ -134,25 +143,24 @@ impl<'a, W: writer::MakeTransport> Rfc3164RecordWriter<'a, W> {
     #[inline(always)]
     ///Flushes record by sending current buffer to the server
     ///
+    ///Buffer may be sent as multiple chunks when decorations of split do not fit.
+    ///
     ///On success clear buffer.
     pub fn flush(&mut self) -> Result<(), W::Error> {
-        if self.buffer.len() > self.header_size {
-            self.writer.write_buffer(self.buffer.as_str(), self.severity, self.retry_count)?;
-            self.clear();
-        }
-
+        self.send_chunks(&mut "", true)?;
+        self.chunker.reset();
         Ok(())
     }
 }
 
-impl<'a, W: writer::MakeTransport> Drop for Rfc3164RecordWriter<'a, W> {
+impl<'a, W: writer::MakeTransport, const N: usize> Drop for RecordWriter<'a, W, N> {
     #[inline(always)]
     fn drop(&mut self) {
         self.buffer.clear()
     }
 }
 
-impl<'a, W: writer::MakeTransport> fmt::Write for Rfc3164RecordWriter<'a, W> {
+impl<'a, W: writer::MakeTransport, const N: usize> fmt::Write for RecordWriter<'a, W, N> {
     #[inline]
     fn write_str(&mut self, text: &str) -> fmt::Result {
         if self.write_str(text).is_err() {
@@ -163,12 +171,35 @@ impl<'a, W: writer::MakeTransport> fmt::Write for Rfc3164RecordWriter<'a, W> {
     }
 }
 
+///Buffer type to hold max possible message as per RFC 3164 (1024 bytes)
+pub type Rfc3164Buffer = str_buf::StrBuf<{ str_buf::capacity(1024) }>;
+
+///RFC 3164 record writer, limited to 1024 bytes by default
+pub type Rfc3164RecordWriter<'a, W, const N: usize = { str_buf::capacity(1024) }> = RecordWriter<'a, W, N>;
+
+///Buffer type to hold max possible message as per RFC 5424 (2048 bytes)
+///
+///RFC only requires receivers to accept messages up to 480 bytes, but recommends support of 2048 bytes
+pub type Rfc5424Buffer = str_buf::StrBuf<{ str_buf::capacity(2048) }>;
+
+///RFC 5424 record writer, limited to 2048 bytes by default
+///
+///Every chunk of record includes structured data.
+pub type Rfc5424RecordWriter<'a, W, const N: usize = { str_buf::capacity(2048) }> = RecordWriter<'a, W, N>;
+
 ///Syslogger
 pub struct Syslog {
     facility: syslog::Facility,
     hostname: syslog::header::Hostname,
     tag: syslog::header::Tag,
     retry_count: u8,
+    retry_policy: writer::RetryPolicy,
+    split: split::Split,
+    sanitize: sanitize::SanitizePolicy,
+    max_size: usize,
+    //Fixed offset from UTC in minutes, or `None` to use local offset of the system
+    utc_offset: Option<i16>,
+    clock: &'static (dyn syslog::header::Clock + Sync),
 }
 
 impl Syslog {
@@ -180,9 +211,89 @@ impl Syslog {
             tag,
             hostname,
             retry_count: 2,
+            retry_policy: writer::RetryPolicy::new(),
+            split: split::Split::new(split::SplitStrategy::Hard),
+            sanitize: sanitize::SanitizePolicy::PassThrough,
+            max_size: usize::MAX,
+            utc_offset: Some(0),
+            clock: &syslog::header::SystemClock,
         }
     }
 
+    #[cfg(feature = "std")]
+    ///Creates new syslog instance with hostname and tag detected from the environment
+    ///
+    ///Hostname is detected via `Hostname::from_system` and tag via `Tag::from_current_exe`, each falling back to `-` when it cannot be detected.
+    ///
+    ///To use FQDN instead, override hostname with `Hostname::from_system_fqdn` via `with_hostname`.
+    pub fn from_env(facility: syslog::Facility) -> Self {
+        let hostname = match syslog::header::Hostname::from_system() {
+            Some(hostname) => hostname,
+            None => syslog::header::Hostname::new_lossy(""),
+        };
+        let tag = match syslog::header::Tag::from_current_exe() {
+            Some(tag) => tag,
+            None => syslog::header::Tag::new_lossy(""),
+        };
+        Self::new(facility, hostname, tag)
+    }
+
+    #[inline(always)]
+    ///Sets hostname of records
+    pub const fn with_hostname(mut self, hostname: syslog::header::Hostname) -> Self {
+        self.hostname = hostname;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets source of time for records.
+    ///
+    ///Time returned by `clock` is converted to configured offset from UTC.
+    ///
+    ///Defaults to `SystemClock`
+    pub const fn with_clock(mut self, clock: &'static (dyn syslog::header::Clock + Sync)) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets fixed offset from UTC in minutes, which time of records is written at.
+    ///
+    ///RFC 3164 has no means to specify timezone, so timestamp is written as wall-clock time at offset.
+    ///
+    ///RFC 5424 timestamp is written as time at offset together with offset.
+    ///
+    ///Offset never changes, so to follow local timezone of the system, including DST changes, use `with_local_offset` instead.
+    ///
+    ///Offset is clamped to `syslog::header::MAX_UTC_OFFSET` (i.e. `23:59`) in either direction.
+    ///
+    ///Defaults to 0 (i.e. UTC)
+    pub const fn with_utc_offset(mut self, utc_offset: i16) -> Self {
+        self.utc_offset = Some(syslog::header::clamp_offset(utc_offset));
+        self
+    }
+
+    #[inline(always)]
+    ///Sets time of records to be written as local time of the system.
+    ///
+    ///Local offset from UTC is determined via `syslog::header::local_offset` for every record, so that it follows DST changes, falling back to UTC when it cannot be determined.
+    ///
+    ///This is commonly expected by syslog daemons from RFC 3164 timestamp, which has no means to specify timezone.
+    pub const fn with_local_offset(mut self) -> Self {
+        self.utc_offset = None;
+        self
+    }
+
+    #[inline]
+    ///Returns current time of record
+    fn timestamp(&self) -> syslog::header::Timestamp {
+        let utc_offset = match self.utc_offset {
+            Some(utc_offset) => utc_offset,
+            None => syslog::header::local_offset().unwrap_or_default(),
+        };
+        self.clock.now().to_offset(utc_offset)
+    }
+
     #[inline(always)]
     ///Changes retry count of attempts to re-try write.
     ///
@@ -196,15 +307,144 @@ impl Syslog {
         self
     }
 
+    #[inline(always)]
+    ///Sets policy of retries: delay between attempts and circuit breaker.
+    ///
+    ///Async loggers await delays using tokio timer instead of policy's `Timer`.
+    ///
+    ///Defaults to immediate retries without circuit breaker
+    pub const fn with_retry_policy(mut self, retry_policy: writer::RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets how records, that do not fit single chunk, are split.
+    ///
+    ///Defaults to hard split without continuation markers
+    pub const fn with_split(mut self, split: split::Split) -> Self {
+        self.split = split;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets policy to handle control characters (e.g. new line) within record.
+    ///
+    ///Policy applies to message, as well as key values of `log` records and fields of `tracing` events.
+    ///
+    ///Structured data supplied directly to record writer is written as it is, but its values can be sanitized via `SanitizePolicy::display`.
+    ///
+    ///Note that `SplitStrategy::Newline` can only find new lines with `SanitizePolicy::PassThrough`.
+    ///
+    ///Defaults to `SanitizePolicy::PassThrough`
+    pub const fn with_sanitize(mut self, sanitize: sanitize::SanitizePolicy) -> Self {
+        self.sanitize = sanitize;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets max size of single record in bytes, including header.
+    ///
+    ///Records exceeding it are split or truncated as configured by `with_split`.
+    ///
+    ///Every record has room for at least 4 bytes of text after header (i.e. single character), so size below that is exceeded rather than making no progress.
+    ///
+    ///Size cannot exceed capacity of buffer used by logger, so to go beyond default limits loggers need to use larger buffer (e.g. `Rfc3164BufferedLogger<W, { str_buf::capacity(8192) }>`)
+    ///
+    ///Defaults to capacity of buffer (1024 bytes for RFC 3164 and 2048 bytes for RFC 5424 by default)
+    pub const fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    #[inline(always)]
+    ///Returns max size of record within buffer of `capacity`
+    const fn record_size(&self, capacity: usize) -> usize {
+        if self.max_size < capacity {
+            self.max_size
+        } else {
+            capacity
+        }
+    }
+
     #[inline(always)]
     ///Creates RFC-3164 format logger using specified `writer`
     pub const fn rfc3164<W: writer::MakeTransport>(self, writer: W) -> Rfc3164Logger<W> {
         Rfc3164Logger::new(self, writer)
     }
 
+    ///Writes RFC 3164 header followed by space, returning its size
+    fn write_rfc3164_header<const N: usize>(&self, buffer: &mut str_buf::StrBuf<N>, severity: Severity) -> usize {
+        let timestamp = self.timestamp();
+        let header = syslog::header::Rfc3164 {
+            pri: severity.priority(self.facility),
+            hostname: &self.hostname,
+            tag: &self.tag,
+            pid: os_id::process::get_raw_id() as _,
+            timestamp,
+        };
+
+        header.write_buffer(buffer);
+        buffer.push_str(" ");
+        buffer.len()
+    }
+
+    ///Writes RFC 5424 header and structured data followed by space, returning its size
+    ///
+    ///Structured data is written straight into `buffer`, as long as record still has room for message, otherwise it is omitted.
+    fn write_rfc5424_header<const N: usize>(&self, buffer: &mut str_buf::StrBuf<N>, severity: Severity, msg_id: Option<&syslog::header::MsgId>, structured_data: Option<&syslog::structured_data::StructuredData>) -> usize {
+        let timestamp = self.timestamp();
+        let header = syslog::header::Rfc5424 {
+            pri: severity.priority(self.facility),
+            hostname: &self.hostname,
+            tag: &self.tag,
+            pid: os_id::process::get_raw_id() as _,
+            timestamp,
+            msg_id,
+        };
+
+        header.write_buffer(buffer);
+        buffer.push_str(" ");
+        match structured_data {
+            Some(structured_data) if buffer.len() + structured_data.as_str().len() + 1 + split::MIN_LIMIT <= self.record_size(str_buf::StrBuf::<N>::capacity()) => structured_data.write_buffer(buffer),
+            _ => {
+                buffer.push_str("-");
+            }
+        }
+        buffer.push_str(" ");
+        buffer.len()
+    }
+
+    #[inline(always)]
+    pub(crate) fn rfc3164_record<'a, W: writer::MakeTransport, const N: usize>(&'a self, writer: &'a mut Writer<W>, buffer: &'a mut str_buf::StrBuf<N>, severity: Severity) -> Rfc3164RecordWriter<'a, W, N> {
+        let header_size = self.write_rfc3164_header(buffer, severity);
+        RecordWriter::new(self, writer, buffer, severity, header_size)
+    }
+
+    #[inline(always)]
+    ///Creates RFC-5424 format logger using specified `writer`
+    pub const fn rfc5424<W: writer::MakeTransport>(self, writer: W) -> Rfc5424Logger<W> {
+        Rfc5424Logger::new(self, writer)
+    }
+
+    #[cfg(feature = "tokio")]
+    #[inline(always)]
+    ///Creates RFC-3164 format logger using specified async `writer`
+    pub const fn rfc3164_async<W: writer::AsyncMakeTransport>(self, writer: W) -> Rfc3164AsyncLogger<W> {
+        Rfc3164AsyncLogger::new(self, writer)
+    }
+
+    #[cfg(feature = "tokio")]
+    #[inline(always)]
+    ///Creates RFC-5424 format logger using specified async `writer`
+    pub const fn rfc5424_async<W: writer::AsyncMakeTransport>(self, writer: W) -> Rfc5424AsyncLogger<W> {
+        Rfc5424AsyncLogger::new(self, writer)
+    }
+
     #[inline(always)]
-    pub(crate) fn rfc3164_record<'a, W: writer::MakeTransport>(&'a self, writer: &'a mut Writer<W>, buffer: &'a mut Rfc3164Buffer, severity: Severity) -> Rfc3164RecordWriter<'a, W> {
-        Rfc3164RecordWriter::new(self, writer, buffer, severity)
+    pub(crate) fn rfc5424_record<'a, W: writer::MakeTransport, const N: usize>(&'a self, writer: &'a mut Writer<W>, buffer: &'a mut str_buf::StrBuf<N>, severity: Severity, msg_id: Option<&syslog::header::MsgId>, structured_data: Option<&syslog::structured_data::StructuredData>) -> Rfc5424RecordWriter<'a, W, N> {
+        let header_size = self.write_rfc5424_header(buffer, severity, msg_id, structured_data);
+        RecordWriter::new(self, writer, buffer, severity, header_size)
     }
 }
 
@@ -229,31 +469,38 @@ impl<W: writer::MakeTransport> Rfc3164Logger<W> {
         Rfc3164BufferedLogger::new(self)
     }
 
+    #[cfg(feature = "std")]
+    ///Converts logger into one that can be shared between threads
+    pub fn shared(self) -> Rfc3164SharedLogger<W> {
+        Rfc3164SharedLogger::new(self)
+    }
+
     #[inline(always)]
     ///Writes specified string onto syslog
     ///
     ///If text doesn't fit limit of 1024 bytes, then it is split into chunks
-    pub fn write_str(&mut self, buffer: &mut Rfc3164Buffer, severity: Severity, text: &str) -> Result<(), W::Error> {
+    pub fn write_str<const N: usize>(&mut self, buffer: &mut str_buf::StrBuf<N>, severity: Severity, text: &str) -> Result<(), W::Error> {
         let mut record = self.syslog.rfc3164_record(&mut self.writer, buffer, severity);
 
-        record.write_str(text)?;
-        record.flush_without_clear()
+        record.write_text(text)
     }
 }
 
-///RFC 3164 logger
-pub struct Rfc3164BufferedLogger<W: writer::MakeTransport> {
+///RFC 3164 logger with internal buffer
+///
+///Buffer size `N` can be increased to allow records above 1024 bytes (e.g. `str_buf::capacity(8192)`)
+pub struct Rfc3164BufferedLogger<W: writer::MakeTransport, const N: usize = { str_buf::capacity(1024) }> {
     inner: Rfc3164Logger<W>,
-    buffer: Rfc3164Buffer,
+    buffer: str_buf::StrBuf<N>,
 }
 
-impl<W: writer::MakeTransport> Rfc3164BufferedLogger<W> {
+impl<W: writer::MakeTransport, const N: usize> Rfc3164BufferedLogger<W, N> {
     #[inline(always)]
     ///Creates new instance of logger with internal buffer
     pub const fn new(inner: Rfc3164Logger<W>) -> Self {
         Self {
             inner,
-            buffer: Rfc3164Buffer::new(),
+            buffer: str_buf::StrBuf::new(),
         }
     }
 
@@ -267,7 +514,265 @@ impl<W: writer::MakeTransport> Rfc3164BufferedLogger<W> {
 
     #[inline(always)]
     ///Creates syslog record writer
-    pub fn write_record(&mut self, severity: Severity) -> Rfc3164RecordWriter<'_, W> {
-        Rfc3164RecordWriter::new(&self.inner.syslog, &mut self.inner.writer, &mut self.buffer, severity)
+    pub fn write_record(&mut self, severity: Severity) -> Rfc3164RecordWriter<'_, W, N> {
+        self.inner.syslog.rfc3164_record(&mut self.inner.writer, &mut self.buffer, severity)
+    }
+}
+
+///RFC 5424 logger
+pub struct Rfc5424Logger<W: writer::MakeTransport> {
+    syslog: Syslog,
+    writer: Writer<W>,
+}
+
+impl<W: writer::MakeTransport> Rfc5424Logger<W> {
+    #[inline(always)]
+    ///Creates new RFC 5424 format logger
+    pub const fn new(syslog: Syslog, writer: W) -> Self {
+        Self {
+            syslog,
+            writer: Writer::new(writer),
+        }
+    }
+
+    ///Adds internal buffer to the logger
+    pub const fn with_buffer(self) -> Rfc5424BufferedLogger<W> {
+        Rfc5424BufferedLogger::new(self)
+    }
+
+    #[cfg(feature = "std")]
+    ///Converts logger into one that can be shared between threads
+    pub fn shared(self) -> Rfc5424SharedLogger<W> {
+        Rfc5424SharedLogger::new(self)
+    }
+
+    #[inline(always)]
+    ///Writes specified string onto syslog
+    ///
+    ///`msg_id` identifies type of message and omitted when `None`
+    ///
+    ///If text doesn't fit limit of 2048 bytes, then it is split into chunks
+    pub fn write_str<const N: usize>(&mut self, buffer: &mut str_buf::StrBuf<N>, severity: Severity, msg_id: Option<&syslog::header::MsgId>, text: &str) -> Result<(), W::Error> {
+        let mut record = self.syslog.rfc5424_record(&mut self.writer, buffer, severity, msg_id, None);
+
+        record.write_text(text)
+    }
+
+    #[inline(always)]
+    ///Creates syslog record writer with optional `msg_id` and `structured_data`
+    pub fn write_record<'a, const N: usize>(&'a mut self, buffer: &'a mut str_buf::StrBuf<N>, severity: Severity, msg_id: Option<&syslog::header::MsgId>, structured_data: Option<&syslog::structured_data::StructuredData>) -> Rfc5424RecordWriter<'a, W, N> {
+        self.syslog.rfc5424_record(&mut self.writer, buffer, severity, msg_id, structured_data)
+    }
+}
+
+///RFC 5424 logger with internal buffer
+///
+///Buffer size `N` can be increased to allow records above 2048 bytes (e.g. `str_buf::capacity(8192)`)
+pub struct Rfc5424BufferedLogger<W: writer::MakeTransport, const N: usize = { str_buf::capacity(2048) }> {
+    inner: Rfc5424Logger<W>,
+    buffer: str_buf::StrBuf<N>,
+}
+
+impl<W: writer::MakeTransport, const N: usize> Rfc5424BufferedLogger<W, N> {
+    #[inline(always)]
+    ///Creates new instance of logger with internal buffer
+    pub const fn new(inner: Rfc5424Logger<W>) -> Self {
+        Self {
+            inner,
+            buffer: str_buf::StrBuf::new(),
+        }
+    }
+
+    #[inline(always)]
+    ///Writes specified string onto syslog
+    ///
+    ///`msg_id` identifies type of message and omitted when `None`
+    ///
+    ///If text doesn't fit limit of 2048 bytes, then it is split into chunks
+    pub fn write_str(&mut self, severity: Severity, msg_id: Option<&syslog::header::MsgId>, text: &str) -> Result<(), W::Error> {
+        self.inner.write_str(&mut self.buffer, severity, msg_id, text)
+    }
+
+    #[inline(always)]
+    ///Creates syslog record writer with optional `msg_id` and `structured_data`
+    pub fn write_record(&mut self, severity: Severity, msg_id: Option<&syslog::header::MsgId>, structured_data: Option<&syslog::structured_data::StructuredData>) -> Rfc5424RecordWriter<'_, W, N> {
+        self.inner.write_record(&mut self.buffer, severity, msg_id, structured_data)
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline(always)]
+fn lock_writer<W: writer::MakeTransport>(writer: &std::sync::Mutex<Writer<W>>) -> std::sync::MutexGuard<'_, Writer<W>> {
+    //Writer only holds cached transport, which is dropped on first failure anyway, so poisoning can be ignored
+    match writer.lock() {
+        Ok(writer) => writer,
+        Err(error) => error.into_inner(),
+    }
+}
+
+#[cfg(feature = "std")]
+///RFC 3164 logger which can be shared between threads
+///
+///Transport is cached behind lock, which is held while record is being written, so that chunks of single record are never interleaved.
+///
+///Each call formats record within its own buffer of size `N` on stack.
+pub struct Rfc3164SharedLogger<W: writer::MakeTransport, const N: usize = { str_buf::capacity(1024) }> {
+    syslog: Syslog,
+    writer: std::sync::Mutex<Writer<W>>,
+}
+
+#[cfg(feature = "std")]
+impl<W: writer::MakeTransport, const N: usize> Rfc3164SharedLogger<W, N> {
+    #[inline(always)]
+    ///Creates new instance of logger, re-using `inner` transport
+    pub fn new(inner: Rfc3164Logger<W>) -> Self {
+        Self {
+            syslog: inner.syslog,
+            writer: std::sync::Mutex::new(inner.writer),
+        }
+    }
+
+    ///Writes specified string onto syslog
+    ///
+    ///If text doesn't fit limit of 1024 bytes, then it is split into chunks
+    pub fn write_str(&self, severity: Severity, text: &str) -> Result<(), W::Error> {
+        let mut buffer = str_buf::StrBuf::<N>::new();
+        let mut writer = lock_writer(&self.writer);
+        let mut record = self.syslog.rfc3164_record(&mut writer, &mut buffer, severity);
+
+        record.write_text(text)
+    }
+}
+
+#[cfg(feature = "std")]
+///RFC 5424 logger which can be shared between threads
+///
+///Transport is cached behind lock, which is held while record is being written, so that chunks of single record are never interleaved.
+///
+///Each call formats record within its own buffer of size `N` on stack.
+pub struct Rfc5424SharedLogger<W: writer::MakeTransport, const N: usize = { str_buf::capacity(2048) }> {
+    syslog: Syslog,
+    writer: std::sync::Mutex<Writer<W>>,
+}
+
+#[cfg(feature = "std")]
+impl<W: writer::MakeTransport, const N: usize> Rfc5424SharedLogger<W, N> {
+    #[inline(always)]
+    ///Creates new instance of logger, re-using `inner` transport
+    pub fn new(inner: Rfc5424Logger<W>) -> Self {
+        Self {
+            syslog: inner.syslog,
+            writer: std::sync::Mutex::new(inner.writer),
+        }
+    }
+
+    #[inline(always)]
+    ///Writes specified string onto syslog
+    ///
+    ///`msg_id` identifies type of message and omitted when `None`
+    ///
+    ///If text doesn't fit limit of 2048 bytes, then it is split into chunks
+    pub fn write_str(&self, severity: Severity, msg_id: Option<&syslog::header::MsgId>, text: &str) -> Result<(), W::Error> {
+        self.write_structured_str(severity, msg_id, None, text)
+    }
+
+    ///Writes specified string onto syslog with optional `msg_id` and `structured_data`
+    ///
+    ///If text doesn't fit limit of 2048 bytes, then it is split into chunks, each including structured data
+    pub fn write_structured_str(&self, severity: Severity, msg_id: Option<&syslog::header::MsgId>, structured_data: Option<&syslog::structured_data::StructuredData>, text: &str) -> Result<(), W::Error> {
+        let mut buffer = str_buf::StrBuf::<N>::new();
+        let mut writer = lock_writer(&self.writer);
+        let mut record = self.syslog.rfc5424_record(&mut writer, &mut buffer, severity, msg_id, structured_data);
+
+        record.write_text(text)
+    }
+}
+
+#[cfg(feature = "tokio")]
+///Writes `text` after header of `header_size`, splitting it into chunks when it doesn't fit `buffer`
+///
+///Same as `write_str` of record writers followed by final flush
+async fn write_chunks_async<W: writer::AsyncMakeTransport, const N: usize>(writer: &mut writer::AsyncWriter<W>, buffer: &mut str_buf::StrBuf<N>, mut chunker: split::Chunker, severity: Severity, retry_count: u8, retry_policy: &writer::RetryPolicy, mut text: &str) -> Result<(), W::Error> {
+    chunker.start(buffer, text);
+    while let Some(cut) = chunker.next_chunk(buffer, &mut text, true) {
+        writer.write_buffer(chunker.chunk(buffer, cut), severity, retry_count, retry_policy).await?;
+        chunker.commit(buffer, cut);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "tokio")]
+///RFC 3164 logger using async transport
+pub struct Rfc3164AsyncLogger<W: writer::AsyncMakeTransport, const N: usize = { str_buf::capacity(1024) }> {
+    syslog: Syslog,
+    writer: writer::AsyncWriter<W>,
+    buffer: str_buf::StrBuf<N>,
+}
+
+#[cfg(feature = "tokio")]
+impl<W: writer::AsyncMakeTransport, const N: usize> Rfc3164AsyncLogger<W, N> {
+    #[inline(always)]
+    ///Creates new RFC 3164 format logger with internal buffer
+    pub const fn new(syslog: Syslog, writer: W) -> Self {
+        Self {
+            syslog,
+            writer: writer::AsyncWriter::new(writer),
+            buffer: str_buf::StrBuf::new(),
+        }
+    }
+
+    ///Writes specified string onto syslog
+    ///
+    ///If text doesn't fit limit of 1024 bytes, then it is split into chunks
+    pub async fn write_str(&mut self, severity: Severity, text: &str) -> Result<(), W::Error> {
+        self.buffer.clear();
+        let header_size = self.syslog.write_rfc3164_header(&mut self.buffer, severity);
+        let chunker = split::Chunker::new(&self.syslog, header_size, str_buf::StrBuf::<N>::capacity());
+        let result = write_chunks_async(&mut self.writer, &mut self.buffer, chunker, severity, self.syslog.retry_count, &self.syslog.retry_policy, text).await;
+        self.buffer.clear();
+        result
+    }
+}
+
+#[cfg(feature = "tokio")]
+///RFC 5424 logger using async transport
+pub struct Rfc5424AsyncLogger<W: writer::AsyncMakeTransport, const N: usize = { str_buf::capacity(2048) }> {
+    syslog: Syslog,
+    writer: writer::AsyncWriter<W>,
+    buffer: str_buf::StrBuf<N>,
+}
+
+#[cfg(feature = "tokio")]
+impl<W: writer::AsyncMakeTransport, const N: usize> Rfc5424AsyncLogger<W, N> {
+    #[inline(always)]
+    ///Creates new RFC 5424 format logger with internal buffer
+    pub const fn new(syslog: Syslog, writer: W) -> Self {
+        Self {
+            syslog,
+            writer: writer::AsyncWriter::new(writer),
+            buffer: str_buf::StrBuf::new(),
+        }
+    }
+
+    #[inline(always)]
+    ///Writes specified string onto syslog
+    ///
+    ///`msg_id` identifies type of message and omitted when `None`
+    ///
+    ///If text doesn't fit limit of 2048 bytes, then it is split into chunks
+    pub async fn write_str(&mut self, severity: Severity, msg_id: Option<&syslog::header::MsgId>, text: &str) -> Result<(), W::Error> {
+        self.write_structured_str(severity, msg_id, None, text).await
+    }
+
+    ///Writes specified string onto syslog with optional `msg_id` and `structured_data`
+    ///
+    ///If text doesn't fit limit of 2048 bytes, then it is split into chunks, each including structured data
+    pub async fn write_structured_str(&mut self, severity: Severity, msg_id: Option<&syslog::header::MsgId>, structured_data: Option<&syslog::structured_data::StructuredData>, text: &str) -> Result<(), W::Error> {
+        self.buffer.clear();
+        let header_size = self.syslog.write_rfc5424_header(&mut self.buffer, severity, msg_id, structured_data);
+        let chunker = split::Chunker::new(&self.syslog, header_size, str_buf::StrBuf::<N>::capacity());
+        let result = write_chunks_async(&mut self.writer, &mut self.buffer, chunker, severity, self.syslog.retry_count, &self.syslog.retry_policy, text).await;
+        self.buffer.clear();
+        result
     }
 }
@@ -2,7 +2,10 @@
 
 use log04::{kv, Log, Metadata, Record, Level, max_level, STATIC_MAX_LEVEL};
 
-use crate::{writer, Writer, Syslog, Severity, Rfc3164Buffer, Rfc3164RecordWriter};
+use crate::{writer, Syslog, Severity, Rfc3164Buffer, Rfc3164RecordWriter, Rfc5424Buffer};
+use crate::syslog::structured_data::{Element, SdId, StructuredData, TruncationPolicy};
+use crate::sanitize::SanitizePolicy;
+use crate::writer::SharedWriter;
 
 use core::fmt;
 
@@ -10,17 +13,19 @@ use core::fmt;
 ///
 ///When [key values](https://docs.rs/log/0.4.22/log/struct.Record.html#method.key_values) is
 ///available appends it after message in format of [KV [<key>=<value>]...]
-pub struct Rfc3164Logger<W> {
+///
+///With `std` feature transport `T` is cached between records, otherwise it is created for every record.
+pub struct Rfc3164Logger<W, T = <W as writer::MakeTransport>::Transport> {
     syslog: Syslog,
-    writer: W,
+    writer: SharedWriter<W, T>,
 }
 
-impl<W> Rfc3164Logger<W> {
+impl<W: writer::MakeTransport> Rfc3164Logger<W> {
     ///Creates new instance
     pub const fn new(syslog: Syslog, writer: W) -> Self {
         Self {
             syslog,
-            writer,
+            writer: SharedWriter::new(writer),
         }
     }
 }
@@ -49,29 +54,28 @@ impl<W: Sync + Send + writer::MakeTransport> Log for Rfc3164Logger<W> where W::T
         let level = record.level().into();
         let args = record.args();
 
-        let mut writer = Writer::new(&self.writer);
         let mut buffer = Rfc3164Buffer::new();
-        let mut syslog = self.syslog.rfc3164_record(&mut writer, &mut buffer, level);
-        if let Some(log) = args.as_str() {
-            if syslog.write_str(log).is_err() {
-                return;
-            }
-        } else {
-            if fmt::Write::write_fmt(&mut syslog, *args).is_err() {
-                return;
+        self.writer.with(|writer| {
+            let mut syslog = self.syslog.rfc3164_record(writer, &mut buffer, level);
+            if let Some(log) = args.as_str() {
+                if syslog.write_str(log).is_err() {
+                    return;
+                }
+            } else {
+                if fmt::Write::write_fmt(&mut syslog, *args).is_err() {
+                    return;
+                }
             }
-        }
 
-        //Visitor will do final flush
-        let mut key_values_writer = StructuredVisitor {
-            record: syslog,
-            //no key values written unless visit() is called
-            is_written: false,
-        };
+            //Visitor will do final flush
+            let mut key_values_writer = StructuredVisitor {
+                record: syslog,
+                //no key values written unless visit() is called
+                is_written: false,
+            };
 
-        if record.key_values().visit(&mut key_values_writer).is_err() {
-            return;
-        }
+            let _ = record.key_values().visit(&mut key_values_writer);
+        })
     }
 
     #[inline(always)]
@@ -115,6 +119,116 @@ impl<'a, W: writer::MakeTransport> Drop for StructuredVisitor<'a, W> {
         if self.is_written {
             let _ = self.record.write_str("]");
         }
-        let _ = self.record.flush_without_clear();
+        let _ = self.record.flush();
+    }
+}
+
+///Syslog with log interface using RFC 5424 format
+///
+///When [key values](https://docs.rs/log/0.4.22/log/struct.Record.html#method.key_values) is
+///available writes it as single SD-ELEMENT with configured SD-ID.
+///
+///Keys that are not valid PARAM-NAME are skipped.
+///
+///With `std` feature transport `T` is cached between records, otherwise it is created for every record.
+pub struct Rfc5424Logger<W, T = <W as writer::MakeTransport>::Transport> {
+    syslog: Syslog,
+    writer: SharedWriter<W, T>,
+    sd_id: SdId,
+    max_value_size: usize,
+    truncation: TruncationPolicy,
+}
+
+impl<W: writer::MakeTransport> Rfc5424Logger<W> {
+    ///Creates new instance, using `sd_id` as SD-ID of key values element
+    ///
+    ///By default each value is limited to 256 bytes and truncated if it exceeds limit
+    pub const fn new(syslog: Syslog, writer: W, sd_id: SdId) -> Self {
+        Self {
+            syslog,
+            writer: SharedWriter::new(writer),
+            sd_id,
+            max_value_size: 256,
+            truncation: TruncationPolicy::Truncate,
+        }
+    }
+
+    ///Sets limit on size of each value (after escaping)
+    ///
+    ///Defaults to 256
+    pub const fn with_max_value_size(mut self, max_value_size: usize) -> Self {
+        self.max_value_size = max_value_size;
+        self
+    }
+
+    ///Sets policy to handle values exceeding limit or available space
+    ///
+    ///Defaults to `TruncationPolicy::Truncate`
+    pub const fn with_truncation(mut self, truncation: TruncationPolicy) -> Self {
+        self.truncation = truncation;
+        self
+    }
+}
+
+impl<W: Sync + Send + writer::MakeTransport> Log for Rfc5424Logger<W> where W::Transport: Sync + Send {
+    #[inline(always)]
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= max_level() && metadata.level() <= STATIC_MAX_LEVEL
+    }
+
+    #[inline]
+    fn log(&self, record: &Record) {
+        let level = record.level().into();
+        let args = record.args();
+
+        let mut structured_data = StructuredData::new();
+        let key_values = record.key_values();
+        if key_values.count() > 0 {
+            if let Some(element) = structured_data.element(&self.sd_id) {
+                let mut key_values_writer = StructuredDataVisitor {
+                    element,
+                    max_value_size: self.max_value_size,
+                    truncation: self.truncation,
+                    sanitize: self.syslog.sanitize,
+                };
+                let _ = key_values.visit(&mut key_values_writer);
+            }
+        }
+
+        let mut buffer = Rfc5424Buffer::new();
+        self.writer.with(|writer| {
+            let mut syslog = self.syslog.rfc5424_record(writer, &mut buffer, level, None, Some(&structured_data));
+            if let Some(log) = args.as_str() {
+                if syslog.write_str(log).is_err() {
+                    return;
+                }
+            } else {
+                if fmt::Write::write_fmt(&mut syslog, *args).is_err() {
+                    return;
+                }
+            }
+
+            let _ = syslog.flush();
+        })
+    }
+
+    #[inline(always)]
+    fn flush(&self) {
+    }
+}
+
+struct StructuredDataVisitor<'a> {
+    element: Element<'a>,
+    max_value_size: usize,
+    truncation: TruncationPolicy,
+    sanitize: SanitizePolicy,
+}
+
+impl<'a> kv::VisitSource<'_> for StructuredDataVisitor<'a> {
+    #[inline(always)]
+    fn visit_pair(&mut self, key: kv::Key<'_>, value: kv::Value<'_>) -> Result<(), kv::Error> {
+        //Pairs that cannot be written are skipped
+        self.element.param_fmt_limited(key.as_str(), format_args!("{}", self.sanitize.display(value)), self.max_value_size, self.truncation);
+        Ok(())
     }
 }
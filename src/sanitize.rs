@@ -0,0 +1,136 @@
+//!Sanitization of control characters within records
+//!
+//!Control characters (e.g. new line) can break framing of transport and allow to inject fake records.
+//!
+//!Policy is applied to message and values of structured data produced from `log` key values and `tracing` fields.
+
+use core::fmt;
+
+use str_buf::StrBuf;
+
+type Replacement = StrBuf<{ str_buf::capacity(4) }>;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Policy to handle ASCII control characters (`0x00..=0x1F` and `0x7F`)
+pub enum SanitizePolicy {
+    ///Character is escaped as `#` followed by its octal code (e.g. `#012` for new line), same as rsyslog does
+    Escape,
+    ///Character is replaced with space
+    Space,
+    ///Character is written as it is
+    PassThrough,
+}
+
+impl SanitizePolicy {
+    #[inline(always)]
+    //Returns position of first control character that needs to be replaced
+    pub(crate) fn find(self, text: &str) -> Option<usize> {
+        match self {
+            Self::PassThrough => None,
+            Self::Escape | Self::Space => text.bytes().position(|byte| byte.is_ascii_control()),
+        }
+    }
+
+    #[inline]
+    //Returns replacement of control character `byte`
+    pub(crate) fn replace(self, byte: u8) -> Replacement {
+        let mut replacement = Replacement::new();
+        match self {
+            Self::Escape => {
+                let _ = fmt::Write::write_fmt(&mut replacement, format_args!("#{byte:03o}"));
+            },
+            Self::Space => {
+                replacement.push_str(" ");
+            },
+            Self::PassThrough => {
+                replacement.push_str(char::from(byte).encode_utf8(&mut [0; 4]));
+            },
+        }
+        replacement
+    }
+
+    //Moves `idx` within sanitized `text` back to the start of escape sequence, if it is inside one
+    pub(crate) fn floor_boundary(self, text: &str, idx: usize) -> usize {
+        if self != Self::Escape {
+            return idx;
+        }
+
+        let bytes = text.as_bytes();
+        let mut start = idx.saturating_sub(3);
+        while start < idx {
+            if bytes[start] == b'#' && start + 4 <= bytes.len() && bytes[start + 1..start + 4].iter().all(|byte| (b'0'..=b'7').contains(byte)) {
+                return start;
+            }
+            start += 1;
+        }
+        idx
+    }
+
+    #[inline]
+    //Returns first character that `ch` is written as
+    pub(crate) fn first_char(self, ch: char) -> char {
+        if !ch.is_ascii_control() {
+            return ch;
+        }
+
+        match self {
+            Self::Escape => '#',
+            Self::Space => ' ',
+            Self::PassThrough => ch,
+        }
+    }
+}
+
+impl SanitizePolicy {
+    #[inline(always)]
+    ///Wraps `value` to apply policy when it is formatted
+    ///
+    ///Can be used to sanitize PARAM-VALUE of structured data (e.g. `element.param_fmt("key", format_args!("{}", policy.display(&value)))`)
+    pub const fn display<T: fmt::Display>(self, value: T) -> Sanitized<T> {
+        Sanitized {
+            value,
+            policy: self,
+        }
+    }
+}
+
+impl Default for SanitizePolicy {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::PassThrough
+    }
+}
+
+///Value which is formatted according to sanitize policy
+pub struct Sanitized<T> {
+    value: T,
+    policy: SanitizePolicy,
+}
+
+impl<T: fmt::Display> fmt::Display for Sanitized<T> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.policy == SanitizePolicy::PassThrough {
+            fmt::Display::fmt(&self.value, fmt)
+        } else {
+            fmt::Write::write_fmt(&mut Sanitizer { out: fmt, policy: self.policy }, format_args!("{}", self.value))
+        }
+    }
+}
+
+///Writer which applies policy to everything written into `out`
+pub(crate) struct Sanitizer<'a, W> {
+    pub(crate) out: &'a mut W,
+    pub(crate) policy: SanitizePolicy,
+}
+
+impl<'a, W: fmt::Write> fmt::Write for Sanitizer<'a, W> {
+    fn write_str(&mut self, mut text: &str) -> fmt::Result {
+        while let Some(idx) = self.policy.find(text) {
+            self.out.write_str(&text[..idx])?;
+            self.out.write_str(self.policy.replace(text.as_bytes()[idx]).as_str())?;
+            text = &text[idx + 1..];
+        }
+        self.out.write_str(text)
+    }
+}
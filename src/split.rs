@@ -0,0 +1,523 @@
+//!Splitting of records, which do not fit single chunk
+//!
+//!Every chunk is sent with common header, while text is split according to `Split` configuration.
+//!
+//!Record that fits single chunk is always sent as it is, without any decorations.
+//!
+//!Alternatively record can be truncated to single chunk, discarding the rest of text.
+//!
+//!Decorations of chunk are composed within record's buffer, so that unless record size is limited below buffer capacity, room for them is kept free once record may be split.
+
+use core::fmt;
+
+use str_buf::StrBuf;
+
+use crate::Syslog;
+use crate::sanitize::SanitizePolicy;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Strategy to select where text is split
+///
+///Split is always done on UTF-8 character boundary.
+pub enum SplitStrategy {
+    ///Splits at last character that fits chunk
+    Hard,
+    ///Splits at last whitespace that fits chunk, falling back to `Hard`
+    ///
+    ///Whitespace at which text is split is omitted.
+    Whitespace,
+    ///Splits at last new line that fits chunk, falling back to `Whitespace`
+    ///
+    ///New line at which text is split is omitted.
+    Newline,
+}
+
+impl SplitStrategy {
+    //Returns end of chunk within `window` and start of the rest, skipping separator
+    //
+    //`next` is character following `window`, if known
+    fn cut(self, window: &str, next: Option<char>) -> (usize, usize) {
+        let len = window.len();
+        match self {
+            Self::Hard => (len, len),
+            Self::Whitespace => match next {
+                Some(next) if next.is_whitespace() => (len, len + next.len_utf8()),
+                _ => match window.char_indices().rev().find(|(_, ch)| ch.is_whitespace()) {
+                    //Separator at the start would result in empty chunk
+                    Some((idx, ch)) if idx > 0 => (idx, idx + ch.len_utf8()),
+                    _ => (len, len),
+                },
+            },
+            Self::Newline => match next {
+                Some('\n') => (len, len + 1),
+                _ => match window.rfind('\n') {
+                    Some(idx) if idx > 0 => (idx, idx + 1),
+                    _ => Self::Whitespace.cut(window, next),
+                },
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+///Configuration of record splitting
+///
+///Defaults to hard split without any decorations
+pub struct Split {
+    strategy: SplitStrategy,
+    marker: Option<&'static str>,
+    is_indexed: bool,
+    is_truncated: bool,
+}
+
+impl Split {
+    #[inline(always)]
+    ///Creates new configuration with specified `strategy`
+    pub const fn new(strategy: SplitStrategy) -> Self {
+        Self {
+            strategy,
+            marker: None,
+            is_indexed: false,
+            is_truncated: false,
+        }
+    }
+
+    #[inline(always)]
+    ///Sets continuation marker (e.g. `...`)
+    ///
+    ///Marker is appended to every chunk that is continued and prepended to every continuation chunk.
+    pub const fn with_marker(mut self, marker: &'static str) -> Self {
+        self.marker = Some(marker);
+        self
+    }
+
+    #[inline(always)]
+    ///Sets whether to prefix every chunk with its part index (e.g. `[2/3] `)
+    ///
+    ///Total number of parts is only known when whole text is written at once (e.g. `write_str` of logger), otherwise index is written as `[2] `
+    pub const fn with_index(mut self, is_indexed: bool) -> Self {
+        self.is_indexed = is_indexed;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets whether to truncate record to single chunk instead of splitting it
+    ///
+    ///Text is truncated at the point selected by strategy and marker, if any, is appended to it, while the rest of text is discarded.
+    ///
+    ///Index is never written for truncated record.
+    pub const fn with_truncation(mut self, is_truncated: bool) -> Self {
+        self.is_truncated = is_truncated;
+        self
+    }
+
+    #[inline(always)]
+    ///Returns strategy used to split text
+    pub const fn strategy(&self) -> SplitStrategy {
+        self.strategy
+    }
+}
+
+impl Default for Split {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new(SplitStrategy::Hard)
+    }
+}
+
+#[inline]
+fn floor_char_boundary(text: &str, mut idx: usize) -> usize {
+    if idx >= text.len() {
+        return text.len();
+    }
+
+    while !text.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+//Min size of text within chunk, fitting any single character or its replacement
+pub(crate) const MIN_LIMIT: usize = 4;
+
+#[inline]
+fn digits(mut num: usize) -> usize {
+    let mut result = 1;
+    while num >= 10 {
+        num /= 10;
+        result += 1;
+    }
+    result
+}
+
+#[derive(Copy, Clone)]
+//Chunk to be sent out of buffer's text
+pub(crate) struct Cut {
+    end: usize,
+    rest: usize,
+    is_last: bool,
+    //Size of decorations composed around chunk's text
+    prefix: usize,
+    suffix: usize,
+}
+
+#[derive(Copy, Clone)]
+//State of record being split into chunks
+pub(crate) struct Chunker {
+    split: Split,
+    sanitize: SanitizePolicy,
+    header_size: usize,
+    //Max size of text within single chunk
+    limit: usize,
+    //Index of next chunk, starting from 1
+    part: usize,
+    total: Option<usize>,
+    //Size of separator at the start of text, that is yet to be written
+    skip: usize,
+    //Whether record is truncated, so that the rest of its text is discarded
+    is_discarding: bool,
+}
+
+impl Chunker {
+    #[inline(always)]
+    pub(crate) const fn new(syslog: &Syslog, header_size: usize, capacity: usize) -> Self {
+        Self {
+            split: syslog.split,
+            sanitize: syslog.sanitize,
+            header_size,
+            limit: Self::limit(syslog.record_size(capacity), header_size, capacity),
+            part: 1,
+            total: None,
+            skip: 0,
+            is_discarding: false,
+        }
+    }
+
+    //Returns max size of text within chunk, which always fits at least single character or its replacement, unless buffer is full with header
+    const fn limit(record_size: usize, header_size: usize, capacity: usize) -> usize {
+        let limit = record_size.saturating_sub(header_size);
+        let limit = if limit < MIN_LIMIT { MIN_LIMIT } else { limit };
+        let room = capacity.saturating_sub(header_size);
+        if limit > room { room } else { limit }
+    }
+
+    #[inline(always)]
+    pub(crate) fn header_size(&self) -> usize {
+        self.header_size
+    }
+
+    //Prepares to write whole `text` as single record, so that total number of parts is known
+    //
+    //`buffer` must contain only header
+    pub(crate) fn start<const N: usize>(&mut self, buffer: &mut StrBuf<N>, text: &str) {
+        self.reset();
+        if self.is_indexed() {
+            //Size of index depends on number of digits in total, so repeat until it is stable
+            let mut total = 1;
+            loop {
+                let count = self.count(buffer, text, total);
+                if count == total {
+                    break;
+                }
+                total = count;
+            }
+            self.total = Some(total);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn reset(&mut self) {
+        self.part = 1;
+        self.total = None;
+        self.skip = 0;
+        self.is_discarding = false;
+    }
+
+    #[inline(always)]
+    fn is_indexed(&self) -> bool {
+        self.split.is_indexed && !self.split.is_truncated
+    }
+
+    fn prefix_size(&self, part: usize, total: Option<usize>) -> usize {
+        let mut size = 0;
+        if self.is_indexed() {
+            size += digits(part) + 3;
+            if let Some(total) = total {
+                size += digits(total) + 1;
+            }
+        }
+        if part > 1 {
+            size += self.suffix_size();
+        }
+        size
+    }
+
+    #[inline(always)]
+    fn suffix_size(&self) -> usize {
+        match self.split.marker {
+            Some(marker) => marker.len(),
+            None => 0,
+        }
+    }
+
+    //Returns size up to which buffer is filled with text, keeping room for decorations of current and next chunk
+    fn fill_size<const N: usize>(&self) -> usize {
+        let size = self.header_size + self.limit;
+        let reserve = match self.total {
+            //Record is known to fit single chunk, which is sent as it is
+            Some(1) => 0,
+            total => self.prefix_size(self.part + 1, total) + self.suffix_size(),
+        };
+        let fill_size = StrBuf::<N>::capacity().saturating_sub(reserve);
+        if fill_size >= size {
+            size
+        } else {
+            //Decorations are omitted if buffer cannot fit them together with single character
+            core::cmp::max(fill_size, core::cmp::min(size, self.header_size + MIN_LIMIT))
+        }
+    }
+
+    //Selects chunk out of `body`, followed by `pending` text that doesn't fit buffer
+    fn decide(&self, body: &str, pending: &str) -> Cut {
+        let (part, total) = (self.part, self.total);
+        if pending.is_empty() && (part == 1 || body.len() + self.prefix_size(part, total) <= self.limit) {
+            return Cut {
+                end: body.len(),
+                rest: body.len(),
+                is_last: true,
+                prefix: 0,
+                suffix: 0,
+            };
+        }
+
+        let room = self.limit.saturating_sub(self.prefix_size(part, total) + self.suffix_size());
+        let mut window = self.sanitize.floor_boundary(body, floor_char_boundary(body, room));
+        if window == 0 {
+            //Decorations leave no room, so send at least single character to make progress
+            window = body.chars().next().map_or(0, char::len_utf8);
+        }
+        let next = match body[window..].chars().next() {
+            Some(next) => Some(next),
+            //Pending text is not sanitized yet
+            None => pending.chars().next().map(|next| self.sanitize.first_char(next)),
+        };
+        let (end, rest) = self.split.strategy.cut(&body[..window], next);
+        Cut {
+            end,
+            rest,
+            is_last: false,
+            prefix: 0,
+            suffix: 0,
+        }
+    }
+
+    //Returns number of chunks `text` is split into, assuming `total`
+    //
+    //Text is split exactly as it would be when written, except that nothing is sent
+    fn count<const N: usize>(&self, buffer: &mut StrBuf<N>, mut text: &str, total: usize) -> usize {
+        let mut chunker = *self;
+        chunker.total = Some(total);
+
+        //Text that is written as it is can be split at any byte
+        let is_plain = text.bytes().all(|byte| byte.is_ascii() && !byte.is_ascii_control() && byte != b'#');
+        if is_plain && self.split.strategy == SplitStrategy::Hard {
+            return chunker.count_plain::<N>(text.len());
+        }
+
+        //Otherwise split is done within `buffer`, after header
+        let count = loop {
+            let rest = chunker.push(buffer, text);
+            let body = &buffer.as_str()[self.header_size..];
+            if body.is_empty() {
+                break chunker.part - 1;
+            }
+
+            let cut = chunker.decide(body, rest);
+            if cut.is_last {
+                break chunker.part;
+            }
+            chunker.commit(buffer, cut);
+            text = rest;
+        };
+        //This is safe because we know exact header size written
+        unsafe {
+            buffer.set_len(self.header_size);
+        }
+        count
+    }
+
+    //Returns number of chunks plain text of `size` is split into, same as `count`
+    fn count_plain<const N: usize>(mut self, mut size: usize) -> usize {
+        loop {
+            let body = core::cmp::min(size, self.fill_size::<N>() - self.header_size);
+            if body == 0 {
+                break self.part - 1;
+            }
+
+            let prefix_size = self.prefix_size(self.part, self.total);
+            if body == size && (self.part == 1 || body + prefix_size <= self.limit) {
+                break self.part;
+            }
+            let room = self.limit.saturating_sub(prefix_size + self.suffix_size());
+            size -= core::cmp::max(core::cmp::min(room, body), 1);
+            self.part += 1;
+        }
+    }
+
+    //Appends as much of `text` as fits chunk, returning the rest
+    //
+    //Control characters are replaced according to sanitize policy, while replacement is never split
+    fn push<'a, const N: usize>(&mut self, buffer: &mut StrBuf<N>, mut text: &'a str) -> &'a str {
+        if self.is_discarding {
+            return "";
+        }
+
+        if self.skip > 0 {
+            let skip = core::cmp::min(self.skip, text.len());
+            text = &text[skip..];
+            self.skip -= skip;
+        }
+
+        loop {
+            let room = self.fill_size::<N>().saturating_sub(buffer.len());
+            let control = self.sanitize.find(text);
+            let clean = control.unwrap_or(text.len());
+            let size = floor_char_boundary(text, core::cmp::min(room, clean));
+            buffer.push_str(&text[..size]);
+            if size < clean {
+                break &text[size..];
+            }
+
+            match control {
+                Some(idx) => {
+                    let replacement = self.sanitize.replace(text.as_bytes()[idx]);
+                    if room - size < replacement.len() {
+                        break &text[idx..];
+                    }
+                    buffer.push_str(replacement.as_str());
+                    text = &text[idx + 1..];
+                },
+                None => break "",
+            }
+        }
+    }
+
+    //Composes next chunk at the start of `buffer`, returning cut to commit once chunk is sent
+    //
+    //Decorations are appended to `buffer` and rotated into place, moving the rest of text after the chunk
+    fn compose<const N: usize>(&self, buffer: &mut StrBuf<N>, pending: &str) -> Option<Cut> {
+        let body = &buffer.as_str()[self.header_size..];
+        if body.is_empty() {
+            return None;
+        }
+
+        let mut cut = self.decide(body, pending);
+        let is_split = self.part > 1 || !cut.is_last;
+        let suffix_size = if cut.is_last { 0 } else { self.suffix_size() };
+        if !is_split || buffer.remaining() < self.prefix_size(self.part, self.total) + suffix_size {
+            return Some(cut);
+        }
+
+        let len = buffer.len();
+        if self.is_indexed() {
+            let _ = match self.total {
+                Some(total) => fmt::Write::write_fmt(buffer, format_args!("[{}/{}] ", self.part, total)),
+                None => fmt::Write::write_fmt(buffer, format_args!("[{}] ", self.part)),
+            };
+        }
+        if let Some(marker) = self.split.marker {
+            if self.part > 1 {
+                buffer.push_str(marker);
+            }
+            cut.prefix = buffer.len() - len;
+            if !cut.is_last {
+                buffer.push_str(marker);
+            }
+        } else {
+            cut.prefix = buffer.len() - len;
+        }
+        cut.suffix = buffer.len() - len - cut.prefix;
+
+        //Text, prefix, suffix => prefix, suffix, text => prefix, text, suffix
+        let text = &mut Self::bytes_mut(buffer)[self.header_size..];
+        text.rotate_right(cut.prefix + cut.suffix);
+        text[cut.prefix..cut.prefix + cut.suffix + cut.end].rotate_left(cut.suffix);
+
+        Some(cut)
+    }
+
+    #[inline(always)]
+    //Returns written bytes of `buffer`, which must be kept valid UTF-8
+    fn bytes_mut<const N: usize>(buffer: &mut StrBuf<N>) -> &mut [u8] {
+        //This is safe because slice is within written part of buffer
+        unsafe {
+            core::slice::from_raw_parts_mut(buffer.as_mut_ptr(), buffer.len())
+        }
+    }
+
+    #[inline(always)]
+    //Returns chunk composed for `cut`
+    pub(crate) fn chunk<'a, const N: usize>(&self, buffer: &'a StrBuf<N>, cut: Cut) -> &'a str {
+        &buffer.as_str()[..self.header_size + cut.prefix + cut.end + cut.suffix]
+    }
+
+    //Appends `text` to `buffer`, composing next chunk once buffer is full or, when `is_flush`, until it is empty
+    //
+    //Returns cut to commit once chunk is sent, or `None` when there is nothing to send yet
+    pub(crate) fn next_chunk<const N: usize>(&mut self, buffer: &mut StrBuf<N>, text: &mut &str, is_flush: bool) -> Option<Cut> {
+        *text = self.push(buffer, text);
+        if text.is_empty() && !is_flush {
+            return None;
+        }
+        self.compose(buffer, text)
+    }
+
+    //Removes decorations of chunk that failed to be sent, so that it can be composed again
+    pub(crate) fn revert<const N: usize>(&self, buffer: &mut StrBuf<N>, cut: Cut) {
+        let text = &mut Self::bytes_mut(buffer)[self.header_size..];
+        text[cut.prefix..cut.prefix + cut.suffix + cut.end].rotate_right(cut.suffix);
+        text.rotate_left(cut.prefix + cut.suffix);
+        let len = buffer.len() - cut.prefix - cut.suffix;
+        //This is safe because decorations are at the end of buffer
+        unsafe {
+            buffer.set_len(len);
+        }
+    }
+
+    //Removes sent chunk from `buffer`, keeping the rest of text
+    pub(crate) fn commit<const N: usize>(&mut self, buffer: &mut StrBuf<N>, cut: Cut) {
+        if self.split.is_truncated && !cut.is_last {
+            self.is_discarding = true;
+            //This is safe because we know exact header size written
+            unsafe {
+                buffer.set_len(self.header_size);
+            }
+            return;
+        }
+
+        let decorations = cut.prefix + cut.suffix;
+        let body_size = buffer.len() - self.header_size - decorations;
+        if cut.rest >= body_size {
+            self.skip = cut.rest - body_size;
+            //This is safe because we know exact header size written
+            unsafe {
+                buffer.set_len(self.header_size);
+            }
+        } else {
+            //Rest of text follows chunk's text and decorations
+            let start = self.header_size + decorations + cut.rest;
+            let len = buffer.len();
+            Self::bytes_mut(buffer).copy_within(start..len, self.header_size);
+            //This is safe because rest of text starts at character boundary
+            unsafe {
+                buffer.set_len(self.header_size + len - start);
+            }
+        }
+
+        if cut.is_last {
+            self.reset();
+        } else {
+            self.part += 1;
+        }
+    }
+}
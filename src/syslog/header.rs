@@ -6,9 +6,29 @@ use str_buf::StrBuf;
 
 pub use super::{Facility, Severity};
 
+///Max length of hostname, as per RFC 5424
+const HOSTNAME_SIZE: usize = 255;
+///Max length of DNS label
+const LABEL_SIZE: usize = 63;
+///Max length of tag, as per RFC 5424 APP-NAME
+const TAG_SIZE: usize = 48;
+
+#[inline(always)]
+const fn is_hostname_char(byt: u8) -> bool {
+    //`_` is not allowed in DNS names, but is widely used in host names.
+    //`:` is used by IPv6 addresses
+    byt.is_ascii_alphanumeric() || byt == b'-' || byt == b'_' || byt == b':'
+}
+
+#[inline(always)]
+const fn is_print_us_ascii(byt: u8) -> bool {
+    byt > b' ' && byt < 127
+}
+
+#[derive(Copy, Clone)]
 #[repr(transparent)]
-///Hostname, limited to 64 characters
-pub struct Hostname(StrBuf<{ str_buf::capacity(64) }>);
+///Hostname, limited to 255 characters
+pub struct Hostname(StrBuf<{ str_buf::capacity(HOSTNAME_SIZE) }>);
 
 impl Hostname {
     #[inline]
@@ -26,7 +46,186 @@ impl Hostname {
     #[inline]
     ///Creates new hostname
     ///
-    ///It verifies that name is non-empty alphanumeric string, returning None otherwise.
+    ///It verifies that name is FQDN, host name or IP address: non-empty labels of up to 63 alphanumeric characters, `-`, `_` or `:`, separated by `.`
+    ///
+    ///Absolute FQDN with single trailing `.` (e.g. `example.com.`) is accepted as it is.
+    ///
+    ///Returns None if name is not valid.
+    pub const fn new(name: &str) -> Option<Self> {
+        if name.is_empty() {
+            return None;
+        }
+
+        let buffer = match StrBuf::from_str_checked(name) {
+            Ok(buffer) => buffer,
+            Err(_) => return None,
+        };
+
+        let bytes = buffer.as_slice();
+        let mut idx = 0;
+        let mut label_len = 0;
+        while idx < bytes.len() {
+            let byt = bytes[idx];
+            if byt == b'.' {
+                if label_len == 0 {
+                    return None;
+                }
+                label_len = 0;
+            } else if is_hostname_char(byt) && label_len < LABEL_SIZE {
+                label_len += 1;
+            } else {
+                return None;
+            }
+            idx += 1;
+        }
+
+        //Empty label can only be at the end, i.e. trailing `.` of absolute FQDN
+        Some(Self(buffer))
+    }
+
+    ///Creates new hostname, replacing invalid characters with `-`
+    ///
+    ///Empty labels are removed and labels exceeding 63 characters are truncated, as well as name exceeding 255 characters.
+    ///
+    ///If nothing is left, then hostname is `-`
+    pub fn new_lossy(name: &str) -> Self {
+        let mut buffer = StrBuf::<{ str_buf::capacity(HOSTNAME_SIZE) }>::new();
+        for label in name.split('.') {
+            //Separator is pushed only for non-empty label
+            let prefix = if buffer.is_empty() { "" } else { "." };
+
+            for (label_len, ch) in label.chars().enumerate() {
+                let required = if label_len == 0 { prefix.len() + 1 } else { 1 };
+                if label_len == LABEL_SIZE || buffer.remaining() < required {
+                    break;
+                }
+                if label_len == 0 {
+                    buffer.push_str(prefix);
+                }
+                if ch.is_ascii() && is_hostname_char(ch as u8) {
+                    buffer.push_str(ch.encode_utf8(&mut [0; 4]));
+                } else {
+                    buffer.push_str("-");
+                }
+            }
+        }
+
+        if buffer.is_empty() {
+            buffer.push_str("-");
+        }
+        Self(buffer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Hostname {
+    ///Detects hostname of the system
+    ///
+    ///Hostname is retrieved via `gethostname` on unix and `GetComputerNameExW` (DNS hostname) on windows, while other targets have none.
+    ///
+    ///Invalid characters are replaced as in `new_lossy`, while `None` is returned if hostname cannot be detected.
+    pub fn from_system() -> Option<Self> {
+        let hostname = Self::system_name()?;
+        match hostname.trim() {
+            "" => None,
+            hostname => Some(Self::new_lossy(hostname)),
+        }
+    }
+
+    #[cfg(unix)]
+    fn system_name() -> Option<crate::std::string::String> {
+        extern "C" {
+            fn gethostname(name: *mut u8, len: usize) -> i32;
+        }
+
+        //Extra byte for nul terminator
+        let mut buffer = [0u8; HOSTNAME_SIZE + 1];
+        if unsafe { gethostname(buffer.as_mut_ptr(), buffer.len()) } != 0 {
+            return None;
+        }
+        //Truncated name may be not terminated
+        let len = buffer.iter().position(|byt| *byt == 0).unwrap_or(buffer.len());
+        Some(crate::std::string::String::from_utf8_lossy(&buffer[..len]).into_owned())
+    }
+
+    #[cfg(windows)]
+    fn system_name() -> Option<crate::std::string::String> {
+        #[link(name = "kernel32")]
+        extern "system" {
+            fn GetComputerNameExW(name_type: i32, buffer: *mut u16, size: *mut u32) -> i32;
+        }
+        const COMPUTER_NAME_DNS_HOSTNAME: i32 = 1;
+
+        let mut buffer = [0u16; HOSTNAME_SIZE + 1];
+        let mut size = buffer.len() as u32;
+        if unsafe { GetComputerNameExW(COMPUTER_NAME_DNS_HOSTNAME, buffer.as_mut_ptr(), &mut size) } == 0 {
+            return None;
+        }
+        //On success size excludes nul terminator
+        Some(crate::std::string::String::from_utf16_lossy(&buffer[..size as usize]))
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    #[inline(always)]
+    fn system_name() -> Option<crate::std::string::String> {
+        None
+    }
+
+    ///Detects fully qualified domain name of the system
+    ///
+    ///Hostname detected by `from_system` is resolved using `/etc/hosts`, which is what `hostname -f` does when DNS is not involved.
+    ///
+    ///If hostname cannot be resolved, then it is returned as it is.
+    pub fn from_system_fqdn() -> Option<Self> {
+        let hostname = Self::from_system()?;
+        if hostname.as_str().contains('.') {
+            return Some(hostname);
+        }
+
+        let hosts = match crate::std::fs::read_to_string("/etc/hosts") {
+            Ok(hosts) => hosts,
+            Err(_) => return Some(hostname),
+        };
+        for line in hosts.lines() {
+            let line = match line.find('#') {
+                Some(comment) => &line[..comment],
+                None => line,
+            };
+            //Format is `<address> <canonical name> [aliases...]`
+            let canonical = match line.split_whitespace().nth(1) {
+                Some(canonical) => canonical,
+                None => continue,
+            };
+            let is_matched = line.split_whitespace().skip(1).any(|name| match name.split('.').next() {
+                Some(label) => label.eq_ignore_ascii_case(hostname.as_str()),
+                None => false,
+            });
+            if is_matched && canonical.contains('.') {
+                return Some(Self::new_lossy(canonical));
+            }
+        }
+
+        Some(hostname)
+    }
+}
+
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+///Process name (RFC 5424 APP-NAME), limited to 48 characters
+///
+///Note that `[` and `:` are valid in APP-NAME, but RFC 3164 TAG is followed by `[PID]:`, so RFC 3164 header writes them as `_`
+pub struct Tag(StrBuf<{ str_buf::capacity(TAG_SIZE) }>);
+
+impl Tag {
+    #[inline]
+    ///Gets tag
+    pub const fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    ///Creates new tag with name of the process.
+    ///
+    ///It verifies that name is non-empty string of printable ASCII characters, returning None otherwise.
     pub const fn new(name: &str) -> Option<Self> {
         if name.is_empty() {
             None
@@ -35,8 +234,7 @@ impl Hostname {
                 Ok(buffer) => {
                     let mut idx = 0;
                     loop {
-                        let byt = buffer.as_slice()[idx];
-                        if byt.is_ascii_alphanumeric() || byt == b'-' || byt == b'.' {
+                        if is_print_us_ascii(buffer.as_slice()[idx]) {
                             idx += 1;
                             if idx >= name.len() {
                                 break Some(Self(buffer));
@@ -50,33 +248,90 @@ impl Hostname {
             }
         }
     }
+
+    ///Creates new tag, replacing invalid characters with `_` and truncating it to 48 characters
+    ///
+    ///If name is empty, then tag is `-`
+    pub fn new_lossy(name: &str) -> Self {
+        let mut buffer = StrBuf::<{ str_buf::capacity(TAG_SIZE) }>::new();
+        for ch in name.chars() {
+            if buffer.remaining() == 0 {
+                break;
+            }
+            if ch.is_ascii() && is_print_us_ascii(ch as u8) {
+                buffer.push_str(ch.encode_utf8(&mut [0; 4]));
+            } else {
+                buffer.push_str("_");
+            }
+        }
+
+        if buffer.is_empty() {
+            buffer.push_str("-");
+        }
+        Self(buffer)
+    }
 }
 
+#[cfg(feature = "std")]
+impl Tag {
+    ///Detects name of the current process
+    ///
+    ///Name is file name of `argv[0]`, falling back to file name of current executable and then to `/proc/self/comm`, which is truncated to 15 characters.
+    ///
+    ///Invalid characters are replaced and name is truncated as in `new_lossy`, while `None` is returned if name cannot be detected.
+    pub fn from_current_exe() -> Option<Self> {
+        use crate::std::{env, fs, path};
+
+        let file_name = |exe: path::PathBuf| match exe.file_name() {
+            Some(name) => match name.to_string_lossy().trim() {
+                "" => None,
+                name => Some(Self::new_lossy(name)),
+            },
+            None => None,
+        };
+
+        if let Some(tag) = env::args_os().next().map(path::PathBuf::from).and_then(file_name) {
+            return Some(tag);
+        }
+        if let Some(tag) = env::current_exe().ok().and_then(file_name) {
+            return Some(tag);
+        }
+
+        let comm = fs::read_to_string("/proc/self/comm").ok()?;
+        match comm.trim() {
+            "" => None,
+            comm => Some(Self::new_lossy(comm)),
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
 #[repr(transparent)]
-///Process name
-pub struct Tag(StrBuf<{ str_buf::capacity(32) }>);
+///RFC 5424 message id, identifying type of message
+pub struct MsgId(StrBuf<{ str_buf::capacity(32) }>);
 
-impl Tag {
+impl MsgId {
     #[inline]
-    ///Gets tag
+    ///Gets message id
     pub const fn as_str(&self) -> &str {
         self.0.as_str()
     }
 
-    ///Creates new tag with name of the process.
+    ///Creates new message id.
     ///
-    ///It verifies that name is non-empty alphanumeric string, returning None otherwise.
-    pub const fn new(name: &str) -> Option<Self> {
-        if name.is_empty() {
+    ///It verifies that id is non-empty string of printable ASCII characters, returning None otherwise.
+    pub const fn new(id: &str) -> Option<Self> {
+        if id.is_empty() {
             None
         } else {
-            match StrBuf::from_str_checked(name) {
+            match StrBuf::from_str_checked(id) {
                 Ok(buffer) => {
                     let mut idx = 0;
                     loop {
-                        if buffer.as_slice()[idx].is_ascii_alphanumeric() {
+                        let byt = buffer.as_slice()[idx];
+                        if byt > b' ' && byt < 127 {
                             idx += 1;
-                            if idx >= name.len() {
+                            if idx >= id.len() {
                                 break Some(Self(buffer));
                             }
                         } else {
@@ -90,6 +345,30 @@ impl Tag {
     }
 }
 
+macro_rules! impl_str_traits {
+    ($($name:ident),*) => {$(
+        impl fmt::Debug for $name {
+            #[inline(always)]
+            fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(self.as_str(), fmt)
+            }
+        }
+
+        impl PartialEq for $name {
+            #[inline(always)]
+            fn eq(&self, other: &Self) -> bool {
+                self.as_str() == other.as_str()
+            }
+        }
+
+        impl Eq for $name {
+        }
+    )*};
+}
+
+impl_str_traits!(Hostname, Tag, MsgId);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 ///Timestamp components
 pub struct Timestamp {
     ///Year
@@ -104,6 +383,63 @@ pub struct Timestamp {
     pub min: u8,
     ///Hours since midnight. Range 0-23
     pub hour: u8,
+    ///Microseconds after the second. Range 0-999999
+    ///
+    ///`None` if precision is not available
+    pub micros: Option<u32>,
+    ///Offset from UTC in minutes, which is already applied to time components. Range -1439-1439 (see `MAX_UTC_OFFSET`)
+    ///
+    ///`0` indicates UTC
+    pub offset: i16,
+}
+
+#[cfg(feature = "std")]
+fn unix_now() -> Option<(u64, u32)> {
+    extern crate std;
+
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some((now.as_secs(), now.subsec_micros()))
+}
+
+const SECS_PER_DAY: i64 = 86400;
+///Max offset from UTC in minutes, as TIME-NUMOFFSET cannot exceed `23:59`
+pub const MAX_UTC_OFFSET: i16 = 23 * 60 + 59;
+
+#[inline(always)]
+pub(crate) const fn clamp_offset(offset: i16) -> i16 {
+    if offset > MAX_UTC_OFFSET {
+        MAX_UTC_OFFSET
+    } else if offset < -MAX_UTC_OFFSET {
+        -MAX_UTC_OFFSET
+    } else {
+        offset
+    }
+}
+
+///Returns offset of local time from UTC in minutes, as determined by the system at the moment
+///
+///Returns `None` if local time is not available.
+pub fn local_offset() -> Option<i16> {
+    #[inline(always)]
+    fn secs(time: &time_c::Time) -> i64 {
+        days_from_civil(time.year as i64, time.month as i64, time.month_day as i64) * SECS_PER_DAY + time.hour as i64 * 3600 + time.min as i64 * 60 + time.sec as i64
+    }
+
+    let utc = time_c::Time::now_utc()?;
+    let local = time_c::Time::now_local()?;
+    //Time may advance between calls, so round to minutes
+    let offset = (secs(&local) - secs(&utc) + 30).div_euclid(60);
+    Some(offset.clamp(-(MAX_UTC_OFFSET as i64), MAX_UTC_OFFSET as i64) as i16)
+}
+
+//Reference: https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+const fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
 }
 
 impl Timestamp {
@@ -116,21 +452,89 @@ impl Timestamp {
             hour: 0,
             min: 0,
             sec: 0,
+            micros: None,
+            offset: 0,
+        }
+    }
+
+    ///Creates new UTC timestamp from number of seconds since unix epoch
+    pub const fn from_unix(secs: u64, micros: Option<u32>) -> Self {
+        Self::from_unix_signed(secs as i64, micros, 0)
+    }
+
+    //Reference: https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    const fn from_unix_signed(secs: i64, micros: Option<u32>, offset: i16) -> Self {
+        let days = secs.div_euclid(SECS_PER_DAY);
+        let day_secs = secs.rem_euclid(SECS_PER_DAY);
+
+        let days = days + 719468;
+        let era = (if days >= 0 { days } else { days - 146096 }) / 146097;
+        let day_of_era = days - era * 146097;
+        let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month + 2) / 5 + 1;
+        let month = if month < 10 { month + 2 } else { month - 10 };
+        let year = year_of_era + era * 400 + if month <= 1 { 1 } else { 0 };
+
+        Self {
+            year: year as u16,
+            month: month as u8,
+            day: day as u8,
+            hour: (day_secs / 3600) as u8,
+            min: (day_secs % 3600 / 60) as u8,
+            sec: (day_secs % 60) as u8,
+            micros,
+            offset,
+        }
+    }
+
+    ///Converts timestamp into time at specified offset from UTC in minutes
+    ///
+    ///This is useful to present time as local wall-clock time
+    ///
+    ///Offset is clamped to `MAX_UTC_OFFSET` in either direction
+    pub const fn to_offset(self, offset: i16) -> Self {
+        let offset = clamp_offset(offset);
+        if self.offset == offset {
+            return self;
         }
+
+        //Leap second has no representation in unix time
+        let sec = if self.sec > 59 { 59 } else { self.sec };
+        let secs = days_from_civil(self.year as i64, self.month as i64 + 1, self.day as i64) * SECS_PER_DAY
+                   + self.hour as i64 * 3600 + self.min as i64 * 60 + sec as i64
+                   + (offset as i64 - self.offset as i64) * 60;
+        Self::from_unix_signed(secs, self.micros, offset)
     }
 
     ///Creates new current time instance or fallbacks to default UTC time
+    ///
+    ///With `std` feature, it includes microseconds
     pub fn now_utc() -> Self {
-        match time_c::Time::now_utc() {
-            Some(time_c::Time { sec, min, hour, month_day, month, year, .. }) => Self {
-                year,
-                month: month.saturating_sub(1),
-                day: month_day,
-                hour,
-                sec,
-                min,
-            },
-            None => Self::utc(),
+        #[cfg(feature = "std")]
+        {
+            match unix_now() {
+                Some((secs, micros)) => Self::from_unix(secs, Some(micros)),
+                None => Self::utc(),
+            }
+        }
+
+        #[cfg(not(feature = "std"))]
+        {
+            match time_c::Time::now_utc() {
+                Some(time_c::Time { sec, min, hour, month_day, month, year, .. }) => Self {
+                    year,
+                    month: month.saturating_sub(1),
+                    day: month_day,
+                    hour,
+                    sec,
+                    min,
+                    micros: None,
+                    offset: 0,
+                },
+                None => Self::utc(),
+            }
         }
     }
 
@@ -153,6 +557,30 @@ impl Timestamp {
     }
 }
 
+///Source of time for record headers
+pub trait Clock {
+    ///Returns current time
+    fn now(&self) -> Timestamp;
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+///Clock using system time, as provided by `Timestamp::now_utc`
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[inline(always)]
+    fn now(&self) -> Timestamp {
+        Timestamp::now_utc()
+    }
+}
+
+impl<F: Fn() -> Timestamp> Clock for F {
+    #[inline(always)]
+    fn now(&self) -> Timestamp {
+        (self)()
+    }
+}
+
 ///RFC 3164 header to the message
 pub struct Rfc3164<'a> {
     ///Encoded priority
@@ -193,7 +621,15 @@ impl<'a> Rfc3164<'a> {
         let hostname = hostname.as_str();
         let month = timestamp.rfc3164_month();
         let Timestamp { day, hour, sec, min, .. } = timestamp;
-        let _ = fmt::Write::write_fmt(out, format_args!("<{pri}>{month} {day:>2} {hour:>02}:{min:>02}:{sec:>02} {hostname} {tag}[{pid}]:"));
+        let _ = fmt::Write::write_fmt(out, format_args!("<{pri}>{month} {day:>2} {hour:>02}:{min:>02}:{sec:>02} {hostname} "));
+        //TAG ends at `[` or `:`, so they are replaced to keep header parseable
+        for ch in tag.chars() {
+            let _ = match ch {
+                '[' | ':' => fmt::Write::write_char(out, '_'),
+                ch => fmt::Write::write_char(out, ch),
+            };
+        }
+        let _ = fmt::Write::write_fmt(out, format_args!("[{pid}]:"));
     }
 
     ///Creates static sized string that holds content of header
@@ -205,6 +641,8 @@ impl<'a> Rfc3164<'a> {
 }
 
 ///RFC 5424 header to the message
+///
+///STRUCTURED-DATA is not part of the header and should be written after it, separated by space (see `StructuredData::write_buffer`)
 pub struct Rfc5424<'a> {
     ///Encoded priority
     pub pri: u8,
@@ -218,16 +656,17 @@ pub struct Rfc5424<'a> {
     ///
     ///While it is optional, it should be always available so there is no need not to include it
     pub pid: u32,
-    ///Message id. Use `-` to omit
-    pub msg_id: &'a Tag,
+    ///Message id. Use `None` to omit
+    pub msg_id: Option<&'a MsgId>,
 }
 
 const RFC_5424_SIZE: usize = 3 + 2 //Prio(u8 integer) wrapped in <>
-    + 20 + 1 //Timestamp
+    + 1 + 1 //Version which is always 1
+    + 19 + 7 + 6 + 1 //Timestamp with optional fraction of second and offset
     + mem::size_of::<Hostname>() - 1 + 1 //TLS certificate limit is used arbitrary, but generally it should not be longer than 23 characters. -1 for Hostname length byte
     + mem::size_of::<Tag>() - 1 + 1 //Process name(tag) type uses extra byte for length so -1
     + 10 + 1 //Optional PID component(u32 integer)
-    + mem::size_of::<Tag>() - 1; //Message id (limit by tag length) and -1 for byte length
+    + mem::size_of::<MsgId>() - 1; //Message id and -1 for byte length
 
 impl Rfc5424<'_> {
     ///Max possible header size
@@ -237,15 +676,31 @@ impl Rfc5424<'_> {
     ///
     ///It assumes `out` will be successful because I only use it like that
     ///
-    ///On success writes `Rfc3164::SIZE` bytes long string
+    ///On success writes up to `Rfc5424::SIZE` bytes long string
     pub fn write_buffer(&self, out: &mut impl fmt::Write) {
         let Self { pri, timestamp, hostname, tag, pid, msg_id } = self;
         let tag = tag.as_str();
         let hostname = hostname.as_str();
         let month = timestamp.month.wrapping_add(1);
-        let msg_id = msg_id.as_str();
-        let Timestamp { year, day, hour, sec, min, .. } = timestamp;
-        let _ = fmt::Write::write_fmt(out, format_args!("<{pri}>{year:>04}-{month:>02}-{day:>02}T{hour:>02}:{min:>02}:{sec:>02}Z {hostname} {tag} {pid} {msg_id}"));
+        let msg_id = match msg_id {
+            Some(msg_id) => msg_id.as_str(),
+            None => "-",
+        };
+        let Timestamp { year, day, hour, sec, min, micros, offset, .. } = timestamp;
+        let _ = fmt::Write::write_fmt(out, format_args!("<{pri}>1 {year:>04}-{month:>02}-{day:>02}T{hour:>02}:{min:>02}:{sec:>02}"));
+        if let Some(micros) = micros {
+            let _ = fmt::Write::write_fmt(out, format_args!(".{micros:>06}"));
+        }
+        let _ = match *offset {
+            0 => fmt::Write::write_str(out, "Z"),
+            offset => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.unsigned_abs();
+                let (offset_hour, offset_min) = (offset / 60, offset % 60);
+                fmt::Write::write_fmt(out, format_args!("{sign}{offset_hour:>02}:{offset_min:>02}"))
+            }
+        };
+        let _ = fmt::Write::write_fmt(out, format_args!(" {hostname} {tag} {pid} {msg_id}"));
     }
 
     ///Creates static sized string that holds content of header
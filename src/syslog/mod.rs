@@ -3,11 +3,13 @@
 //! Reference: syslog.h
 
 pub mod header;
+pub mod structured_data;
+pub mod parser;
 
 ///Log importance
 #[repr(u8)]
 #[allow(non_camel_case_types)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Severity {
     ///system is unusable
     LOG_EMERG = 0,
@@ -32,12 +34,26 @@ impl Severity {
     pub const fn priority(self, fac: Facility) -> u8 {
         fac as u8 | self as u8
     }
+
+    ///Decodes severity from priority
+    pub const fn from_priority(pri: u8) -> Self {
+        match pri & 0b111 {
+            0 => Self::LOG_EMERG,
+            1 => Self::LOG_ALERT,
+            2 => Self::LOG_CRIT,
+            3 => Self::LOG_ERR,
+            4 => Self::LOG_WARNING,
+            5 => Self::LOG_NOTICE,
+            6 => Self::LOG_INFO,
+            _ => Self::LOG_DEBUG,
+        }
+    }
 }
 
 ///Facility code, indicating source of log
 #[repr(u8)]
 #[allow(non_camel_case_types)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Facility {
     ///Kernel
     LOG_KERN = 0 << 3,
@@ -63,6 +79,14 @@ pub enum Facility {
     LOG_AUTHPRIV = 10 << 3,
     ///FTP daemon
     LOG_FTP = 11 << 3,
+    ///NTP subsystem
+    LOG_NTP = 12 << 3,
+    ///Log audit
+    LOG_SECURITY = 13 << 3,
+    ///Log alert
+    LOG_CONSOLE = 14 << 3,
+    ///Clock daemon
+    LOG_CLOCK = 15 << 3,
     ///Reserved for local use
     LOG_LOCAL0 = 16 << 3,
     ///Reserved for local use
@@ -81,6 +105,41 @@ pub enum Facility {
     LOG_LOCAL7 = 23 << 3,
 }
 
+impl Facility {
+    ///Decodes facility from priority
+    ///
+    ///Returns `None` for facility codes which are not defined (i.e. above 23).
+    pub const fn from_priority(pri: u8) -> Option<Self> {
+        match pri >> 3 {
+            0 => Some(Self::LOG_KERN),
+            1 => Some(Self::LOG_USER),
+            2 => Some(Self::LOG_MAIL),
+            3 => Some(Self::LOG_DAEMON),
+            4 => Some(Self::LOG_AUTH),
+            5 => Some(Self::LOG_SYSLOG),
+            6 => Some(Self::LOG_LPR),
+            7 => Some(Self::LOG_NEWS),
+            8 => Some(Self::LOG_UUCP),
+            9 => Some(Self::LOG_CRON),
+            10 => Some(Self::LOG_AUTHPRIV),
+            11 => Some(Self::LOG_FTP),
+            12 => Some(Self::LOG_NTP),
+            13 => Some(Self::LOG_SECURITY),
+            14 => Some(Self::LOG_CONSOLE),
+            15 => Some(Self::LOG_CLOCK),
+            16 => Some(Self::LOG_LOCAL0),
+            17 => Some(Self::LOG_LOCAL1),
+            18 => Some(Self::LOG_LOCAL2),
+            19 => Some(Self::LOG_LOCAL3),
+            20 => Some(Self::LOG_LOCAL4),
+            21 => Some(Self::LOG_LOCAL5),
+            22 => Some(Self::LOG_LOCAL6),
+            23 => Some(Self::LOG_LOCAL7),
+            _ => None,
+        }
+    }
+}
+
 impl Default for Facility {
     #[inline(always)]
     fn default() -> Self {
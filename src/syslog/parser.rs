@@ -0,0 +1,689 @@
+//! Parser of syslog records
+//!
+//! Parsed records borrow from input, so no allocation is required.
+//!
+//! Reference: [RFC 3164](https://datatracker.ietf.org/doc/html/rfc3164), [RFC 5424](https://datatracker.ietf.org/doc/html/rfc5424)
+
+use core::fmt;
+
+use super::{Facility, Severity};
+use super::header::{Hostname, MsgId, Tag, Timestamp};
+use super::structured_data::{self, SdId};
+
+const NIL: &str = "-";
+const BOM: &str = "\u{feff}";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Part of the record which failed to parse
+pub enum ErrorKind {
+    ///PRI is not `<number>` with known facility
+    Pri,
+    ///RFC 5424 version is not `1`
+    Version,
+    ///Timestamp is malformed or out of range
+    Timestamp,
+    ///Hostname is not valid `Hostname`
+    Hostname,
+    ///Tag (APP-NAME) is not valid `Tag`
+    Tag,
+    ///PID is not a number or PROCID is not 1 to 128 printable ASCII characters
+    Pid,
+    ///MSGID is not valid `MsgId`
+    MsgId,
+    ///STRUCTURED-DATA is malformed
+    StructuredData,
+    ///Separator between header fields is missing
+    Separator,
+    ///Record ends before header is complete
+    UnexpectedEnd,
+}
+
+impl ErrorKind {
+    #[inline]
+    const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pri => "PRI",
+            Self::Version => "VERSION",
+            Self::Timestamp => "TIMESTAMP",
+            Self::Hostname => "HOSTNAME",
+            Self::Tag => "TAG",
+            Self::Pid => "PID",
+            Self::MsgId => "MSGID",
+            Self::StructuredData => "STRUCTURED-DATA",
+            Self::Separator => "separator",
+            Self::UnexpectedEnd => "end of record",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Record parsing error
+pub struct ParseError {
+    ///Part of the record which is malformed
+    pub kind: ErrorKind,
+    ///Byte offset within record where malformed part starts
+    pub offset: usize,
+}
+
+impl fmt::Display for ParseError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ErrorKind::UnexpectedEnd => fmt.write_fmt(format_args!("Unexpected end of record at offset {}", self.offset)),
+            kind => fmt.write_fmt(format_args!("Invalid {} at offset {}", kind.as_str(), self.offset)),
+        }
+    }
+}
+
+struct Cursor<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    #[inline(always)]
+    const fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+        }
+    }
+
+    #[inline(always)]
+    const fn error(&self, kind: ErrorKind, offset: usize) -> ParseError {
+        ParseError {
+            kind,
+            offset,
+        }
+    }
+
+    #[inline(always)]
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    #[inline(always)]
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byt: u8, kind: ErrorKind) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(next) if next == byt => {
+                self.pos += 1;
+                Ok(())
+            },
+            Some(_) => Err(self.error(kind, self.pos)),
+            None => Err(self.error(ErrorKind::UnexpectedEnd, self.pos)),
+        }
+    }
+
+    //Takes field up to space, which is consumed
+    fn field(&mut self, kind: ErrorKind) -> Result<(usize, &'a str), ParseError> {
+        let start = self.pos;
+        match self.rest().find(' ') {
+            Some(0) => Err(self.error(kind, start)),
+            Some(len) => {
+                self.pos += len + 1;
+                Ok((start, &self.input[start..start + len]))
+            },
+            None => Err(self.error(ErrorKind::UnexpectedEnd, self.input.len())),
+        }
+    }
+
+    //Takes exactly `len` digits
+    fn digits(&mut self, len: usize, kind: ErrorKind) -> Result<u32, ParseError> {
+        let mut value = 0u32;
+        for _ in 0..len {
+            match self.peek() {
+                Some(byt) if byt.is_ascii_digit() => {
+                    value = value * 10 + u32::from(byt - b'0');
+                    self.pos += 1;
+                },
+                Some(_) => return Err(self.error(kind, self.pos)),
+                None => return Err(self.error(ErrorKind::UnexpectedEnd, self.pos)),
+            }
+        }
+        Ok(value)
+    }
+
+    fn pri(&mut self) -> Result<(Facility, Severity), ParseError> {
+        self.expect(b'<', ErrorKind::Pri)?;
+        let start = self.pos;
+        let end = match self.rest().find('>') {
+            Some(end) if end > 0 && end <= 3 => end,
+            Some(_) => return Err(self.error(ErrorKind::Pri, start)),
+            None => return Err(self.error(ErrorKind::UnexpectedEnd, self.input.len())),
+        };
+        let pri = &self.rest()[..end];
+        //Leading zeros are not allowed
+        if pri.len() > 1 && pri.starts_with('0') {
+            return Err(self.error(ErrorKind::Pri, start));
+        }
+        let pri = match pri.parse::<u8>() {
+            Ok(pri) => pri,
+            Err(_) => return Err(self.error(ErrorKind::Pri, start)),
+        };
+        self.pos += end + 1;
+
+        match Facility::from_priority(pri) {
+            Some(facility) => Ok((facility, Severity::from_priority(pri))),
+            None => Err(self.error(ErrorKind::Pri, start)),
+        }
+    }
+
+    fn proc_id(&self, offset: usize, proc_id: &'a str) -> Result<&'a str, ParseError> {
+        if proc_id.len() <= 128 && proc_id.bytes().all(|byt| byt > b' ' && byt < 127) {
+            Ok(proc_id)
+        } else {
+            Err(self.error(ErrorKind::Pid, offset))
+        }
+    }
+
+    fn pid(&self, offset: usize, pid: &str) -> Result<u32, ParseError> {
+        if pid.bytes().all(|byt| byt.is_ascii_digit()) {
+            if let Ok(pid) = pid.parse() {
+                return Ok(pid);
+            }
+        }
+        Err(self.error(ErrorKind::Pid, offset))
+    }
+
+    fn time(&mut self) -> Result<(u8, u8, u8), ParseError> {
+        let start = self.pos;
+        let hour = self.digits(2, ErrorKind::Timestamp)?;
+        self.expect(b':', ErrorKind::Timestamp)?;
+        let min = self.digits(2, ErrorKind::Timestamp)?;
+        self.expect(b':', ErrorKind::Timestamp)?;
+        let sec = self.digits(2, ErrorKind::Timestamp)?;
+        if hour > 23 || min > 59 || sec > 60 {
+            Err(self.error(ErrorKind::Timestamp, start))
+        } else {
+            Ok((hour as u8, min as u8, sec as u8))
+        }
+    }
+
+    fn rfc3164_timestamp(&mut self) -> Result<Timestamp, ParseError> {
+        const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+        let start = self.pos;
+        let month = match self.input.as_bytes().get(start..start + 3) {
+            Some(month) => match MONTHS.iter().position(|name| name.as_bytes() == month) {
+                Some(month) => month as u8,
+                None => return Err(self.error(ErrorKind::Timestamp, start)),
+            },
+            None => return Err(self.error(ErrorKind::UnexpectedEnd, self.input.len())),
+        };
+        self.pos += 3;
+        self.expect(b' ', ErrorKind::Timestamp)?;
+        //Day is padded with space
+        if self.peek() == Some(b' ') {
+            self.pos += 1;
+        }
+        let day_start = self.pos;
+        let mut day = self.digits(1, ErrorKind::Timestamp)?;
+        if let Some(byt @ b'0'..=b'9') = self.peek() {
+            day = day * 10 + u32::from(byt - b'0');
+            self.pos += 1;
+        }
+        if day == 0 || day > 31 {
+            return Err(self.error(ErrorKind::Timestamp, day_start));
+        }
+        self.expect(b' ', ErrorKind::Timestamp)?;
+        let (hour, min, sec) = self.time()?;
+
+        Ok(Timestamp {
+            year: 0,
+            month,
+            day: day as u8,
+            hour,
+            min,
+            sec,
+            micros: None,
+            offset: 0,
+        })
+    }
+
+    fn rfc5424_timestamp(&mut self) -> Result<Timestamp, ParseError> {
+        let start = self.pos;
+        let year = self.digits(4, ErrorKind::Timestamp)?;
+        self.expect(b'-', ErrorKind::Timestamp)?;
+        let month = self.digits(2, ErrorKind::Timestamp)?;
+        self.expect(b'-', ErrorKind::Timestamp)?;
+        let day = self.digits(2, ErrorKind::Timestamp)?;
+        self.expect(b'T', ErrorKind::Timestamp)?;
+        if month == 0 || month > 12 || day == 0 || day > 31 {
+            return Err(self.error(ErrorKind::Timestamp, start));
+        }
+        let (hour, min, sec) = self.time()?;
+
+        let micros = if self.peek() == Some(b'.') {
+            self.pos += 1;
+            let fraction_start = self.pos;
+            let mut micros = 0;
+            let mut len = 0;
+            while let Some(byt) = self.peek() {
+                if !byt.is_ascii_digit() {
+                    break;
+                }
+                //Reject before value overflows
+                if len == 6 {
+                    return Err(self.error(ErrorKind::Timestamp, fraction_start));
+                }
+                micros = micros * 10 + u32::from(byt - b'0');
+                len += 1;
+                self.pos += 1;
+            }
+            if len == 0 {
+                return Err(self.error(ErrorKind::Timestamp, fraction_start));
+            }
+            Some(micros * 10u32.pow(6 - len))
+        } else {
+            None
+        };
+
+        let offset = match self.peek() {
+            Some(b'Z') => {
+                self.pos += 1;
+                0
+            },
+            Some(sign @ (b'+' | b'-')) => {
+                let offset_start = self.pos;
+                self.pos += 1;
+                let offset_hour = self.digits(2, ErrorKind::Timestamp)?;
+                self.expect(b':', ErrorKind::Timestamp)?;
+                let offset_min = self.digits(2, ErrorKind::Timestamp)?;
+                if offset_hour > 23 || offset_min > 59 {
+                    return Err(self.error(ErrorKind::Timestamp, offset_start));
+                }
+                let offset = (offset_hour * 60 + offset_min) as i16;
+                if sign == b'-' { -offset } else { offset }
+            },
+            Some(_) => return Err(self.error(ErrorKind::Timestamp, self.pos)),
+            None => return Err(self.error(ErrorKind::UnexpectedEnd, self.pos)),
+        };
+
+        Ok(Timestamp {
+            year: year as u16,
+            month: (month - 1) as u8,
+            day: day as u8,
+            hour,
+            min,
+            sec,
+            micros,
+            offset,
+        })
+    }
+
+    //Validates STRUCTURED-DATA, consuming it
+    fn structured_data(&mut self) -> Result<StructuredData<'a>, ParseError> {
+        let start = self.pos;
+        loop {
+            self.expect(b'[', ErrorKind::StructuredData)?;
+            let id_start = self.pos;
+            let id_len = self.rest().find(|ch| ch == ' ' || ch == ']').unwrap_or(self.rest().len());
+            if SdId::new(&self.rest()[..id_len]).is_none() {
+                return Err(self.error(ErrorKind::StructuredData, id_start));
+            }
+            self.pos += id_len;
+
+            loop {
+                match self.peek() {
+                    Some(b']') => {
+                        self.pos += 1;
+                        break;
+                    },
+                    Some(b' ') => {
+                        self.pos += 1;
+                        let name_start = self.pos;
+                        let name_len = self.rest().find('=').unwrap_or(self.rest().len());
+                        if !structured_data::is_valid_param_name(&self.rest()[..name_len]) {
+                            return Err(self.error(ErrorKind::StructuredData, name_start));
+                        }
+                        self.pos += name_len;
+                        self.expect(b'=', ErrorKind::StructuredData)?;
+                        self.expect(b'"', ErrorKind::StructuredData)?;
+                        let value_len = match value_len(self.rest()) {
+                            Some(len) => len,
+                            None => return Err(self.error(ErrorKind::UnexpectedEnd, self.input.len())),
+                        };
+                        self.pos += value_len + 1;
+                    },
+                    Some(_) => return Err(self.error(ErrorKind::StructuredData, self.pos)),
+                    None => return Err(self.error(ErrorKind::UnexpectedEnd, self.pos)),
+                }
+            }
+
+            if self.peek() != Some(b'[') {
+                break Ok(StructuredData(&self.input[start..self.pos]));
+            }
+        }
+    }
+
+    //Returns message after optional separating space
+    fn msg(&self) -> Result<&'a str, ParseError> {
+        match self.peek() {
+            None => Ok(""),
+            Some(b' ') => {
+                let msg = &self.input[self.pos + 1..];
+                Ok(msg.strip_prefix(BOM).unwrap_or(msg))
+            },
+            Some(_) => Err(self.error(ErrorKind::Separator, self.pos)),
+        }
+    }
+}
+
+///Parses PRI, common for both formats, returning it with rest of the record
+pub fn parse_pri(input: &str) -> Result<(Facility, Severity, &str), ParseError> {
+    let mut cursor = Cursor::new(input);
+    let (facility, severity) = cursor.pri()?;
+    Ok((facility, severity, cursor.rest()))
+}
+
+//Returns length of escaped PARAM-VALUE up to closing quote
+fn value_len(value: &str) -> Option<usize> {
+    let mut bytes = value.bytes().enumerate();
+    while let Some((idx, byt)) = bytes.next() {
+        match byt {
+            b'"' => return Some(idx),
+            b'\\' => {
+                bytes.next();
+            },
+            _ => (),
+        }
+    }
+    None
+}
+
+///RFC 3164 record
+///
+///RFC 3164 timestamp has no year, so it is always `0`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rfc3164Record<'a> {
+    ///Facility
+    pub facility: Facility,
+    ///Severity
+    pub severity: Severity,
+    ///Timestamp
+    pub timestamp: Timestamp,
+    ///Hostname
+    pub hostname: Hostname,
+    ///Process name (tag)
+    pub tag: Tag,
+    ///Process pid, if present
+    pub pid: Option<u32>,
+    ///Message
+    pub msg: &'a str,
+}
+
+impl<'a> Rfc3164Record<'a> {
+    ///Parses record in format `<PRI>Mmm dd hh:mm:ss HOSTNAME TAG[PID]: MSG`
+    ///
+    ///PID is optional
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(input);
+        let (facility, severity) = cursor.pri()?;
+        let timestamp = cursor.rfc3164_timestamp()?;
+        cursor.expect(b' ', ErrorKind::Separator)?;
+
+        let (hostname_start, hostname) = cursor.field(ErrorKind::Hostname)?;
+        let hostname = match Hostname::new(hostname) {
+            Some(hostname) => hostname,
+            None => return Err(cursor.error(ErrorKind::Hostname, hostname_start)),
+        };
+
+        let tag_start = cursor.pos;
+        let tag_len = match cursor.rest().find(|ch| ch == '[' || ch == ':') {
+            Some(len) => len,
+            None => return Err(cursor.error(ErrorKind::UnexpectedEnd, input.len())),
+        };
+        let tag = match Tag::new(&cursor.rest()[..tag_len]) {
+            Some(tag) => tag,
+            None => return Err(cursor.error(ErrorKind::Tag, tag_start)),
+        };
+        cursor.pos += tag_len;
+
+        let pid = if cursor.peek() == Some(b'[') {
+            cursor.pos += 1;
+            let pid_start = cursor.pos;
+            let pid_len = match cursor.rest().find(']') {
+                Some(len) => len,
+                None => return Err(cursor.error(ErrorKind::UnexpectedEnd, input.len())),
+            };
+            let pid = cursor.pid(pid_start, &cursor.rest()[..pid_len])?;
+            cursor.pos += pid_len + 1;
+            Some(pid)
+        } else {
+            None
+        };
+        cursor.expect(b':', ErrorKind::Separator)?;
+
+        Ok(Self {
+            facility,
+            severity,
+            timestamp,
+            hostname,
+            tag,
+            pid,
+            msg: cursor.msg()?,
+        })
+    }
+}
+
+///RFC 5424 record
+///
+///Nil values (`-`) are represented as `None`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rfc5424Record<'a> {
+    ///Facility
+    pub facility: Facility,
+    ///Severity
+    pub severity: Severity,
+    ///Timestamp
+    pub timestamp: Option<Timestamp>,
+    ///Hostname
+    pub hostname: Option<Hostname>,
+    ///Process name (APP-NAME)
+    pub tag: Option<Tag>,
+    ///Process id (PROCID)
+    ///
+    ///Unlike RFC 3164 PID, it is arbitrary printable ASCII string
+    pub proc_id: Option<&'a str>,
+    ///Message id
+    pub msg_id: Option<MsgId>,
+    ///Structured data
+    pub structured_data: Option<StructuredData<'a>>,
+    ///Message, without BOM
+    pub msg: &'a str,
+}
+
+impl<'a> Rfc5424Record<'a> {
+    ///Parses record in format `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA [MSG]`
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let mut cursor = Cursor::new(input);
+        let (facility, severity) = cursor.pri()?;
+        let version_start = cursor.pos;
+        if cursor.field(ErrorKind::Version)?.1 != "1" {
+            return Err(cursor.error(ErrorKind::Version, version_start));
+        }
+
+        let timestamp = if cursor.rest().starts_with(NIL) {
+            cursor.pos += NIL.len();
+            None
+        } else {
+            Some(cursor.rfc5424_timestamp()?)
+        };
+        cursor.expect(b' ', ErrorKind::Separator)?;
+
+        let hostname = match cursor.field(ErrorKind::Hostname)? {
+            (_, NIL) => None,
+            (offset, hostname) => match Hostname::new(hostname) {
+                Some(hostname) => Some(hostname),
+                None => return Err(cursor.error(ErrorKind::Hostname, offset)),
+            },
+        };
+        let tag = match cursor.field(ErrorKind::Tag)? {
+            (_, NIL) => None,
+            (offset, tag) => match Tag::new(tag) {
+                Some(tag) => Some(tag),
+                None => return Err(cursor.error(ErrorKind::Tag, offset)),
+            },
+        };
+        let proc_id = match cursor.field(ErrorKind::Pid)? {
+            (_, NIL) => None,
+            (offset, proc_id) => Some(cursor.proc_id(offset, proc_id)?),
+        };
+        let msg_id = match cursor.field(ErrorKind::MsgId)? {
+            (_, NIL) => None,
+            (offset, msg_id) => match MsgId::new(msg_id) {
+                Some(msg_id) => Some(msg_id),
+                None => return Err(cursor.error(ErrorKind::MsgId, offset)),
+            },
+        };
+
+        let structured_data = if cursor.rest().starts_with(NIL) {
+            cursor.pos += NIL.len();
+            None
+        } else {
+            Some(cursor.structured_data()?)
+        };
+
+        Ok(Self {
+            facility,
+            severity,
+            timestamp,
+            hostname,
+            tag,
+            proc_id,
+            msg_id,
+            structured_data,
+            msg: cursor.msg()?,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Validated STRUCTURED-DATA of parsed record
+pub struct StructuredData<'a>(&'a str);
+
+impl<'a> StructuredData<'a> {
+    #[inline(always)]
+    ///Returns raw STRUCTURED-DATA
+    pub const fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    #[inline(always)]
+    ///Returns iterator over SD-ELEMENTs
+    pub const fn elements(&self) -> SdElements<'a> {
+        SdElements(self.0)
+    }
+}
+
+///Iterator over SD-ELEMENTs
+pub struct SdElements<'a>(&'a str);
+
+impl<'a> Iterator for SdElements<'a> {
+    type Item = SdElement<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        //Input is validated, so element starts with `[`
+        let element = self.0.strip_prefix('[')?;
+        let id_len = element.find(|ch| ch == ' ' || ch == ']')?;
+        let id = &element[..id_len];
+
+        let mut params_len = id_len;
+        while element.as_bytes()[params_len] == b' ' {
+            let value_start = params_len + element[params_len..].find('"')? + 1;
+            params_len = value_start + value_len(&element[value_start..])? + 1;
+        }
+        self.0 = &element[params_len + 1..];
+
+        Some(SdElement {
+            id,
+            params: &element[id_len..params_len],
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///SD-ELEMENT of parsed record
+pub struct SdElement<'a> {
+    ///SD-ID
+    pub id: &'a str,
+    params: &'a str,
+}
+
+impl<'a> SdElement<'a> {
+    #[inline(always)]
+    ///Returns iterator over SD-PARAMs
+    pub const fn params(&self) -> SdParams<'a> {
+        SdParams(self.params)
+    }
+}
+
+///Iterator over SD-PARAMs
+pub struct SdParams<'a>(&'a str);
+
+impl<'a> Iterator for SdParams<'a> {
+    type Item = SdParam<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        //Input is validated, so each param is ` name="value"`
+        let param = self.0.strip_prefix(' ')?;
+        let name_len = param.find('=')?;
+        let value = &param[name_len + 2..];
+        let value_len = value_len(value)?;
+        self.0 = &value[value_len + 1..];
+
+        Some(SdParam {
+            name: &param[..name_len],
+            value: SdValue(&value[..value_len]),
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///SD-PARAM of parsed record
+pub struct SdParam<'a> {
+    ///PARAM-NAME
+    pub name: &'a str,
+    ///PARAM-VALUE
+    pub value: SdValue<'a>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Escaped PARAM-VALUE
+///
+///Its `Display` implementation writes unescaped value
+pub struct SdValue<'a>(&'a str);
+
+impl<'a> SdValue<'a> {
+    #[inline(always)]
+    ///Returns value as it is written in record
+    pub const fn as_raw(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl fmt::Display for SdValue<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut value = self.0;
+        while let Some(idx) = value.find('\\') {
+            fmt.write_str(&value[..idx])?;
+            let escaped = &value[idx + 1..];
+            //Only `"`, `\` and `]` are escaped, otherwise backslash is kept as it is
+            match escaped.as_bytes().first() {
+                Some(b'"' | b'\\' | b']') => {
+                    fmt.write_str(&escaped[..1])?;
+                    value = &escaped[1..];
+                },
+                _ => {
+                    fmt.write_str("\\")?;
+                    value = escaped;
+                },
+            }
+        }
+        fmt.write_str(value)
+    }
+}
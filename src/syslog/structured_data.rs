@@ -0,0 +1,387 @@
+//! RFC 5424 STRUCTURED-DATA components
+//!
+//! Reference: [RFC 5424 6.3](https://datatracker.ietf.org/doc/html/rfc5424#section-6.3)
+
+use core::{cmp, fmt};
+
+use str_buf::StrBuf;
+
+///Max size of structured data within single record
+///
+///It is limited to leave sufficient space for message itself within `Rfc5424Buffer`
+pub const SIZE: usize = 1024;
+
+///Max length of SD-NAME
+const NAME_SIZE: usize = 32;
+
+type Buffer = StrBuf<{ str_buf::capacity(SIZE) }>;
+
+#[inline(always)]
+const fn is_name_char(byt: u8) -> bool {
+    //PRINTUSASCII except '=', SP, ']', '"'
+    byt > b' ' && byt < 127 && byt != b'=' && byt != b']' && byt != b'"'
+}
+
+///Checks whether `name` is valid SD-NAME, which is used as PARAM-NAME
+///
+///Name must be 1 to 32 characters long and consist of printable ASCII characters except `=`, ` `, `]` and `"`
+pub const fn is_valid_param_name(name: &str) -> bool {
+    let name = name.as_bytes();
+    if name.is_empty() || name.len() > NAME_SIZE {
+        return false;
+    }
+
+    let mut idx = 0;
+    while idx < name.len() {
+        if !is_name_char(name[idx]) {
+            return false;
+        }
+        idx += 1;
+    }
+
+    true
+}
+
+#[repr(transparent)]
+///SD-ID, identifying structured data element
+///
+///It is either IANA registered name (e.g. `origin`) or enterprise specific name in format `name@<private enterprise number>`
+pub struct SdId(StrBuf<{ str_buf::capacity(NAME_SIZE) }>);
+
+impl SdId {
+    #[inline]
+    ///Gets SD-ID
+    pub const fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    ///Creates new SD-ID
+    ///
+    ///It verifies that `id` is valid SD-NAME, optionally followed by `@` and private enterprise number (e.g. `32473` or `32473.1`), returning None otherwise.
+    pub const fn new(id: &str) -> Option<Self> {
+        if !is_valid_param_name(id) {
+            return None;
+        }
+
+        let bytes = id.as_bytes();
+        let mut idx = 0;
+        while idx < bytes.len() {
+            if bytes[idx] == b'@' {
+                break;
+            }
+            idx += 1;
+        }
+
+        //IANA registered name
+        if idx == bytes.len() {
+            return Some(Self(StrBuf::from_str(id)));
+        } else if idx == 0 {
+            return None;
+        }
+
+        //Enterprise number must be sequence of digits separated by single dot
+        idx += 1;
+        let mut is_digit_expected = true;
+        while idx < bytes.len() {
+            let byt = bytes[idx];
+            if byt.is_ascii_digit() {
+                is_digit_expected = false;
+            } else if byt == b'.' && !is_digit_expected {
+                is_digit_expected = true;
+            } else {
+                return None;
+            }
+            idx += 1;
+        }
+
+        if is_digit_expected {
+            None
+        } else {
+            Some(Self(StrBuf::from_str(id)))
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Policy to handle PARAM-VALUE which exceeds its limit
+pub enum TruncationPolicy {
+    ///Param is omitted
+    Omit,
+    ///Value is cut to fit its limit and ends with `...`
+    Truncate,
+}
+
+impl Default for TruncationPolicy {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::Truncate
+    }
+}
+
+const TRUNCATION_MARKER: &str = "...";
+
+#[inline(always)]
+fn push_all(buffer: &mut Buffer, text: &str) -> bool {
+    buffer.push_str(text) == text.len()
+}
+
+///Writer which escapes `"`, `\` and `]` within PARAM-VALUE
+///
+///It writes as much as possible until buffer reaches `limit`
+struct EscapedValue<'a> {
+    buffer: &'a mut Buffer,
+    limit: usize,
+}
+
+impl EscapedValue<'_> {
+    fn push(&mut self, text: &str) -> bool {
+        let available = self.limit.saturating_sub(self.buffer.len());
+        if text.len() <= available {
+            push_all(self.buffer, text)
+        } else {
+            let mut size = available;
+            while !text.is_char_boundary(size) {
+                size -= 1;
+            }
+            self.buffer.push_str(&text[..size]);
+            false
+        }
+    }
+}
+
+impl fmt::Write for EscapedValue<'_> {
+    fn write_str(&mut self, mut text: &str) -> fmt::Result {
+        while let Some(idx) = text.find(['"', '\\', ']']) {
+            if !self.push(&text[..idx]) {
+                return Err(fmt::Error);
+            }
+            //Escape sequence must never be split
+            if self.limit.saturating_sub(self.buffer.len()) < 2 {
+                return Err(fmt::Error);
+            }
+            self.buffer.push_str("\\");
+            self.buffer.push_str(&text[idx..idx + 1]);
+            text = &text[idx + 1..];
+        }
+
+        if self.push(text) {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+///STRUCTURED-DATA of the RFC 5424 record, limited to `SIZE` bytes
+///
+///Empty structured data is written as nil value `-`
+///
+///Note that RFC requires each SD-ID to be present only once within record and it is up to user to ensure it (e.g. via `contains`)
+pub struct StructuredData {
+    buffer: Buffer,
+}
+
+impl StructuredData {
+    #[inline(always)]
+    ///Creates new empty instance
+    pub const fn new() -> Self {
+        Self {
+            buffer: Buffer::new(),
+        }
+    }
+
+    #[inline(always)]
+    ///Returns whether there is no element written
+    pub const fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    #[inline(always)]
+    ///Returns encoded elements
+    pub const fn as_str(&self) -> &str {
+        self.buffer.as_str()
+    }
+
+    #[inline(always)]
+    ///Removes all elements
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    ///Starts new SD-ELEMENT with specified `id`
+    ///
+    ///Element is closed once returned value is dropped.
+    ///
+    ///Returns `None` if there is no space left for element
+    pub fn element(&mut self, id: &SdId) -> Option<Element<'_>> {
+        let prev_len = self.buffer.len();
+        //Always leave space for closing bracket
+        if push_all(&mut self.buffer, "[") && push_all(&mut self.buffer, id.as_str()) && self.buffer.remaining() > 0 {
+            Some(Element {
+                data: self,
+            })
+        } else {
+            unsafe {
+                self.buffer.set_len(prev_len);
+            }
+            None
+        }
+    }
+
+    ///Re-opens last SD-ELEMENT to add more params
+    ///
+    ///Returns `None` if there is no element
+    pub fn last_element(&mut self) -> Option<Element<'_>> {
+        if self.is_empty() {
+            None
+        } else {
+            //Remove closing bracket which is added back on Drop
+            unsafe {
+                self.buffer.set_len(self.buffer.len() - 1);
+            }
+            Some(Element {
+                data: self,
+            })
+        }
+    }
+
+    ///Returns whether element with specified `id` is already written
+    pub fn contains(&self, id: &SdId) -> bool {
+        let mut rest = self.as_str();
+        while let Some(element) = rest.strip_prefix('[') {
+            let bytes = element.as_bytes();
+            let mut idx = 0;
+            while idx < bytes.len() && bytes[idx] != b' ' && bytes[idx] != b']' {
+                idx += 1;
+            }
+            if &element[..idx] == id.as_str() {
+                return true;
+            }
+
+            //Skip params until closing bracket, which can be escaped only within quoted value
+            let mut is_value = false;
+            while idx < bytes.len() {
+                match bytes[idx] {
+                    b'\\' if is_value => idx += 1,
+                    b'"' => is_value = !is_value,
+                    b']' if !is_value => break,
+                    _ => (),
+                }
+                idx += 1;
+            }
+            rest = element.get(idx + 1..).unwrap_or("");
+        }
+
+        false
+    }
+
+    ///Appends all elements of `other`
+    ///
+    ///Returns `false` if elements do not fit, in which case nothing is written
+    pub fn extend(&mut self, other: &StructuredData) -> bool {
+        if self.buffer.remaining() >= other.buffer.len() {
+            push_all(&mut self.buffer, other.as_str())
+        } else {
+            false
+        }
+    }
+
+    ///Writes structured data into `out`, using `-` if there is no element
+    pub fn write_buffer(&self, out: &mut impl fmt::Write) {
+        let _ = if self.is_empty() {
+            fmt::Write::write_str(out, "-")
+        } else {
+            fmt::Write::write_str(out, self.as_str())
+        };
+    }
+}
+
+impl Default for StructuredData {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for StructuredData {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_buffer(fmt);
+        Ok(())
+    }
+}
+
+///SD-ELEMENT builder
+///
+///On Drop element is closed
+pub struct Element<'a> {
+    data: &'a mut StructuredData,
+}
+
+impl Element<'_> {
+    #[inline]
+    ///Adds SD-PARAM with specified `name` and `value`
+    ///
+    ///Returns `false` if `name` is not valid or param doesn't fit, in which case element is left unchanged
+    pub fn param(&mut self, name: &str, value: &str) -> bool {
+        self.param_fmt(name, format_args!("{value}"))
+    }
+
+    #[inline]
+    ///Adds SD-PARAM with specified `name` and formatted `value`
+    ///
+    ///Returns `false` if `name` is not valid or param doesn't fit, in which case element is left unchanged
+    pub fn param_fmt(&mut self, name: &str, value: fmt::Arguments<'_>) -> bool {
+        self.param_fmt_limited(name, value, SIZE, TruncationPolicy::Omit)
+    }
+
+    ///Adds SD-PARAM with specified `name` and formatted `value`, limiting escaped value to `limit` bytes
+    ///
+    ///If value exceeds `limit` or doesn't fit, then it is handled according to `policy`
+    ///
+    ///Returns `false` if `name` is not valid or param is omitted, in which case element is left unchanged
+    pub fn param_fmt_limited(&mut self, name: &str, value: fmt::Arguments<'_>, limit: usize, policy: TruncationPolicy) -> bool {
+        if !is_valid_param_name(name) {
+            return false;
+        }
+
+        let buffer = &mut self.data.buffer;
+        let prev_len = buffer.len();
+        //Always leave space for closing quote and bracket
+        if !(push_all(buffer, " ") && push_all(buffer, name) && push_all(buffer, "=\"") && buffer.remaining() >= 2) {
+            unsafe {
+                buffer.set_len(prev_len);
+            }
+            return false;
+        }
+
+        let value_start = buffer.len();
+        let value_limit = cmp::min(value_start.saturating_add(limit), SIZE - 2);
+        let mut is_written = fmt::Write::write_fmt(&mut EscapedValue { buffer: &mut *buffer, limit: value_limit }, value).is_ok();
+
+        if !is_written && policy == TruncationPolicy::Truncate && value_limit >= value_start + TRUNCATION_MARKER.len() {
+            unsafe {
+                buffer.set_len(value_start);
+            }
+            let limit = value_limit - TRUNCATION_MARKER.len();
+            let _ = fmt::Write::write_fmt(&mut EscapedValue { buffer: &mut *buffer, limit }, value);
+            is_written = push_all(buffer, TRUNCATION_MARKER);
+        }
+
+        if is_written {
+            buffer.push_str("\"");
+        } else {
+            unsafe {
+                buffer.set_len(prev_len);
+            }
+        }
+        is_written
+    }
+}
+
+impl Drop for Element<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.data.buffer.push_str("]");
+    }
+}
@@ -0,0 +1,626 @@
+//! Utilities for integration tests
+//!
+//! - `SyslogServer` - local server, which receives records on background threads until it is dropped.
+//! - `Mock` - scripted transport, which fails on chosen attempts.
+//! - `FixedClock` - clock, which always returns the same time, so that record header is known upfront.
+//!
+//!```rust
+//!use syslog_client::{Facility, Severity, Syslog};
+//!use syslog_client::syslog::header::{Hostname, Tag};
+//!use syslog_client::testing::SyslogServer;
+//!use syslog_client::writer::transport::Udp;
+//!
+//!let server = SyslogServer::udp().expect("to start server");
+//!let transport = Udp {
+//!    local_port: 0,
+//!    remote_addr: server.addr().expect("to have address"),
+//!};
+//!let syslog = Syslog::new(Facility::LOG_USER, Hostname::new("localhost").unwrap(), Tag::new("test").unwrap());
+//!let mut logger = syslog.rfc5424(transport).with_buffer();
+//!logger.write_str(Severity::LOG_ERR, None, "my error").expect("to send");
+//!
+//!let record = server.find_by_severity(Severity::LOG_ERR).expect("to receive error");
+//!assert_eq!(record.msg(), Some("my error"));
+//!```
+
+extern crate alloc;
+
+use core::{fmt, ops, time};
+use core::sync::atomic::{AtomicBool, Ordering};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::{io, net, path, thread};
+use std::io::Read;
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+use crate::syslog::{Facility, Severity};
+use crate::syslog::header::{Clock, Timestamp};
+use crate::syslog::parser::{self, ParseError, Rfc3164Record, Rfc5424Record};
+use crate::writer::{MakeTransport, Transport, TransportError};
+use crate::writer::transport::{Framing, LOCAL_HOST};
+
+///Time to wait for records in `expect_records`
+pub const TIMEOUT: time::Duration = time::Duration::from_secs(5);
+//Interval to check whether server is stopped
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(50);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+///Record received by server
+pub struct Record(String);
+
+impl Record {
+    #[inline(always)]
+    ///Returns record as it is received
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[inline]
+    ///Returns facility, if record starts with valid PRI
+    pub fn facility(&self) -> Option<Facility> {
+        parser::parse_pri(&self.0).ok().map(|(facility, _, _)| facility)
+    }
+
+    #[inline]
+    ///Returns severity, if record starts with valid PRI
+    pub fn severity(&self) -> Option<Severity> {
+        parser::parse_pri(&self.0).ok().map(|(_, severity, _)| severity)
+    }
+
+    #[inline]
+    ///Returns whether record uses RFC 5424 format, as indicated by version after PRI
+    pub fn is_rfc5424(&self) -> bool {
+        match parser::parse_pri(&self.0) {
+            Ok((_, _, rest)) => rest.starts_with("1 "),
+            Err(_) => false,
+        }
+    }
+
+    #[inline(always)]
+    ///Parses record as RFC 3164
+    pub fn rfc3164(&self) -> Result<Rfc3164Record<'_>, ParseError> {
+        Rfc3164Record::parse(&self.0)
+    }
+
+    #[inline(always)]
+    ///Parses record as RFC 5424
+    pub fn rfc5424(&self) -> Result<Rfc5424Record<'_>, ParseError> {
+        Rfc5424Record::parse(&self.0)
+    }
+
+    ///Returns message of the record, parsing it according to its format
+    pub fn msg(&self) -> Option<&str> {
+        if self.is_rfc5424() {
+            self.rfc5424().ok().map(|record| record.msg)
+        } else {
+            self.rfc3164().ok().map(|record| record.msg)
+        }
+    }
+}
+
+struct Received {
+    records: Mutex<Vec<Record>>,
+    on_record: Condvar,
+    is_stopped: AtomicBool,
+}
+
+impl Received {
+    #[inline(always)]
+    fn records(&self) -> MutexGuard<'_, Vec<Record>> {
+        //Records are only appended, so poisoning can be ignored
+        match self.records.lock() {
+            Ok(records) => records,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    #[inline(always)]
+    fn is_stopped(&self) -> bool {
+        self.is_stopped.load(Ordering::Acquire)
+    }
+
+    fn push(&self, record: &[u8]) {
+        let record = Record(String::from_utf8_lossy(record).into_owned());
+        self.records().push(record);
+        self.on_record.notify_all();
+    }
+}
+
+fn is_timeout(error: &io::Error) -> bool {
+    matches!(error.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+fn run_udp(socket: net::UdpSocket, received: Arc<Received>) {
+    let mut buffer = [0u8; u16::MAX as usize];
+    while !received.is_stopped() {
+        match socket.recv(&mut buffer) {
+            Ok(len) => received.push(&buffer[..len]),
+            Err(error) if is_timeout(&error) => continue,
+            Err(_) => break,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn run_unix(socket: std::os::unix::net::UnixDatagram, received: Arc<Received>) {
+    let mut buffer = [0u8; u16::MAX as usize];
+    while !received.is_stopped() {
+        match socket.recv(&mut buffer) {
+            Ok(len) => received.push(&buffer[..len]),
+            Err(error) if is_timeout(&error) => continue,
+            Err(_) => break,
+        }
+    }
+}
+
+//Extracts all complete records from `buffer`, returning `false` if framing is violated
+fn split_records(framing: Framing, buffer: &mut Vec<u8>, received: &Received) -> bool {
+    loop {
+        match framing {
+            Framing::NonTransparent => match buffer.iter().position(|byt| *byt == b'\n') {
+                Some(end) => {
+                    received.push(&buffer[..end]);
+                    buffer.drain(..=end);
+                },
+                None => break true,
+            },
+            Framing::OctetCounting => {
+                let len_end = match buffer.iter().position(|byt| *byt == b' ') {
+                    Some(len_end) => len_end,
+                    None => break buffer.iter().all(u8::is_ascii_digit),
+                };
+                let len = match core::str::from_utf8(&buffer[..len_end]).ok().and_then(|len| len.parse::<usize>().ok()) {
+                    Some(len) => len,
+                    None => break false,
+                };
+                let end = len_end + 1 + len;
+                if buffer.len() < end {
+                    break true;
+                }
+                received.push(&buffer[len_end + 1..end]);
+                buffer.drain(..end);
+            }
+        }
+    }
+}
+
+fn run_tcp_connection(mut socket: net::TcpStream, framing: Framing, received: Arc<Received>) {
+    if socket.set_read_timeout(Some(POLL_INTERVAL)).is_err() {
+        return;
+    }
+
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while !received.is_stopped() {
+        match socket.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(len) => {
+                buffer.extend_from_slice(&chunk[..len]);
+                if !split_records(framing, &mut buffer, &received) {
+                    return;
+                }
+            },
+            Err(error) if is_timeout(&error) => continue,
+            Err(_) => break,
+        }
+    }
+
+    //Last record may be not terminated
+    if framing == Framing::NonTransparent && !buffer.is_empty() {
+        received.push(&buffer);
+    }
+}
+
+fn run_tcp(listener: net::TcpListener, framing: Framing, received: Arc<Received>) {
+    let mut connections = Vec::new();
+    for socket in listener.incoming() {
+        if received.is_stopped() {
+            break;
+        }
+        if let Ok(socket) = socket {
+            let received = received.clone();
+            connections.push(thread::spawn(move || run_tcp_connection(socket, framing, received)));
+        }
+    }
+
+    for connection in connections {
+        let _ = connection.join();
+    }
+}
+
+enum Address {
+    Net(net::SocketAddr),
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Unix(path::PathBuf),
+}
+
+///In-process syslog server, collecting all received records
+///
+///Server listens on local host with ephemeral port, which can be retrieved via `addr()`
+pub struct SyslogServer {
+    address: Address,
+    is_tcp: bool,
+    received: Arc<Received>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+impl SyslogServer {
+    fn new(address: Address, is_tcp: bool, worker: impl FnOnce(Arc<Received>) + Send + 'static) -> io::Result<Self> {
+        let received = Arc::new(Received {
+            records: Mutex::new(Vec::new()),
+            on_record: Condvar::new(),
+            is_stopped: AtomicBool::new(false),
+        });
+        let worker_received = received.clone();
+        let worker = thread::Builder::new().name("syslog-server".into()).spawn(move || worker(worker_received))?;
+        Ok(Self {
+            address,
+            is_tcp,
+            received,
+            worker: Some(worker),
+        })
+    }
+
+    ///Starts UDP server, where each datagram is single record
+    pub fn udp() -> io::Result<Self> {
+        let socket = net::UdpSocket::bind((LOCAL_HOST, 0))?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+        let address = Address::Net(socket.local_addr()?);
+        Self::new(address, false, move |received| run_udp(socket, received))
+    }
+
+    ///Starts TCP server, which expects records using specified `framing`
+    pub fn tcp(framing: Framing) -> io::Result<Self> {
+        let listener = net::TcpListener::bind((LOCAL_HOST, 0))?;
+        let address = Address::Net(listener.local_addr()?);
+        Self::new(address, true, move |received| run_tcp(listener, framing, received))
+    }
+
+    #[cfg(unix)]
+    ///Starts unix datagram server at `path`, where each datagram is single record
+    ///
+    ///Existing file at `path` is replaced and removed once server is dropped.
+    pub fn unix(path: impl Into<path::PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let _ = std::fs::remove_file(&path);
+        let socket = std::os::unix::net::UnixDatagram::bind(&path)?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))?;
+        Self::new(Address::Unix(path), false, move |received| run_unix(socket, received))
+    }
+
+    #[inline]
+    ///Returns address of UDP or TCP server
+    pub fn addr(&self) -> Option<net::SocketAddr> {
+        match &self.address {
+            Address::Net(addr) => Some(*addr),
+            Address::Unix(_) => None,
+        }
+    }
+
+    #[inline]
+    ///Returns path of unix server
+    pub fn path(&self) -> Option<&path::Path> {
+        match &self.address {
+            Address::Net(_) => None,
+            Address::Unix(path) => Some(path),
+        }
+    }
+
+    #[inline]
+    ///Returns all records received so far
+    pub fn records(&self) -> Vec<Record> {
+        self.received.records().clone()
+    }
+
+    #[inline]
+    ///Removes all records received so far
+    pub fn clear(&self) {
+        self.received.records().clear();
+    }
+
+    ///Waits until at least `count` records are received, returning all of them.
+    ///
+    ///Returns `None` if `timeout` expires first
+    pub fn wait_for(&self, count: usize, timeout: time::Duration) -> Option<Vec<Record>> {
+        let records = self.received.records();
+        let records = match self.received.on_record.wait_timeout_while(records, timeout, |records| records.len() < count) {
+            Ok((records, _)) => records,
+            Err(error) => error.into_inner().0,
+        };
+        if records.len() >= count {
+            Some(records.clone())
+        } else {
+            None
+        }
+    }
+
+    ///Waits up to `TIMEOUT` for exactly `count` records, returning them.
+    ///
+    ///Panics if server receives different number of records
+    pub fn expect_records(&self, count: usize) -> Vec<Record> {
+        match self.wait_for(count, TIMEOUT) {
+            Some(records) if records.len() == count => records,
+            Some(records) => panic!("Expected {} records, but received {}: {:#?}", count, records.len(), records),
+            None => {
+                let records = self.records();
+                panic!("Expected {} records within {:?}, but received {}: {:#?}", count, TIMEOUT, records.len(), records)
+            }
+        }
+    }
+
+    #[inline]
+    ///Returns first received record matching `predicate`
+    pub fn find(&self, predicate: impl FnMut(&&Record) -> bool) -> Option<Record> {
+        self.received.records().iter().find(predicate).cloned()
+    }
+
+    ///Waits up to `TIMEOUT` for first record with `severity`
+    pub fn find_by_severity(&self, severity: Severity) -> Option<Record> {
+        let records = self.received.records();
+        let records = match self.received.on_record.wait_timeout_while(records, TIMEOUT, |records| records.iter().all(|record| record.severity() != Some(severity))) {
+            Ok((records, _)) => records,
+            Err(error) => error.into_inner().0,
+        };
+        records.iter().find(|record| record.severity() == Some(severity)).cloned()
+    }
+}
+
+impl Drop for SyslogServer {
+    fn drop(&mut self) {
+        self.received.is_stopped.store(true, Ordering::Release);
+        //Wake up listener, which is blocked on accept
+        if let (true, Address::Net(addr)) = (self.is_tcp, &self.address) {
+            let _ = net::TcpStream::connect(addr);
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+        #[cfg(unix)]
+        if let Address::Unix(path) = &self.address {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Kind of injected failure
+pub enum Fault {
+    ///Error is not terminal, so transport is retried as it is
+    Transient,
+    ///Error is terminal, so transport is created anew
+    Terminal,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Operation of `Mock`
+pub enum Operation {
+    ///`MakeTransport::create`
+    Create,
+    ///`Transport::write`
+    Write,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Error injected by `Mock`
+pub struct MockError {
+    ///Failed operation
+    pub operation: Operation,
+    ///Number of the operation's attempt, starting from 1
+    pub attempt: usize,
+    ///Kind of failure
+    pub fault: Fault,
+}
+
+impl TransportError for MockError {
+    #[inline(always)]
+    fn is_terminal(&self) -> bool {
+        self.fault == Fault::Terminal
+    }
+}
+
+impl fmt::Display for MockError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operation = match self.operation {
+            Operation::Create => "create",
+            Operation::Write => "write",
+        };
+        fmt.write_fmt(format_args!("Injected {:?} failure of {} attempt #{}", self.fault, operation, self.attempt))
+    }
+}
+
+impl std::error::Error for MockError {
+}
+
+struct Step {
+    first: usize,
+    last: usize,
+    fault: Fault,
+}
+
+struct Script(Vec<Step>);
+
+impl Script {
+    fn push(&mut self, attempts: impl ops::RangeBounds<usize>, fault: Fault) {
+        let first = match attempts.start_bound() {
+            ops::Bound::Included(first) => *first,
+            ops::Bound::Excluded(first) => first.saturating_add(1),
+            ops::Bound::Unbounded => 0,
+        };
+        let last = match attempts.end_bound() {
+            ops::Bound::Included(last) => *last,
+            ops::Bound::Excluded(last) => last.saturating_sub(1),
+            ops::Bound::Unbounded => usize::MAX,
+        };
+        self.0.push(Step {
+            first,
+            last,
+            fault,
+        });
+    }
+
+    #[inline]
+    fn fault(&self, attempt: usize) -> Option<Fault> {
+        self.0.iter().find(|step| step.first <= attempt && attempt <= step.last).map(|step| step.fault)
+    }
+}
+
+struct MockState {
+    create_script: Script,
+    write_script: Script,
+    creates: usize,
+    writes: usize,
+    records: Vec<(Severity, String)>,
+}
+
+///Scripted transport, which fails `create` or `write` on chosen attempts
+///
+///Attempts are counted from 1 across all transports created by the mock, while first matching fault applies.
+///
+///All clones share the same script and counters.
+///
+///```rust
+///use syslog_client::testing::{Fault, Mock};
+///
+///let mock = Mock::new().with_create_fault(1..=2, Fault::Transient).with_write_fault(3.., Fault::Terminal);
+///```
+#[derive(Clone)]
+pub struct Mock {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl Mock {
+    ///Creates new instance, which never fails
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState {
+                create_script: Script(Vec::new()),
+                write_script: Script(Vec::new()),
+                creates: 0,
+                writes: 0,
+                records: Vec::new(),
+            })),
+        }
+    }
+
+    #[inline(always)]
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        //State is always consistent between operations, so poisoning can be ignored
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    ///Adds `fault` to `create` on specified range of `attempts` (e.g. `2..=2` or `3..`)
+    pub fn with_create_fault(self, attempts: impl ops::RangeBounds<usize>, fault: Fault) -> Self {
+        self.state().create_script.push(attempts, fault);
+        self
+    }
+
+    ///Adds `fault` to `write` on specified range of `attempts` (e.g. `2..=2` or `3..`)
+    pub fn with_write_fault(self, attempts: impl ops::RangeBounds<usize>, fault: Fault) -> Self {
+        self.state().write_script.push(attempts, fault);
+        self
+    }
+
+    #[inline]
+    ///Returns number of `create` attempts
+    pub fn creates(&self) -> usize {
+        self.state().creates
+    }
+
+    #[inline]
+    ///Returns number of `write` attempts
+    pub fn writes(&self) -> usize {
+        self.state().writes
+    }
+
+    #[inline]
+    ///Returns successfully written records
+    pub fn records(&self) -> Vec<(Severity, String)> {
+        self.state().records.clone()
+    }
+}
+
+impl Default for Mock {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MakeTransport for Mock {
+    type Error = MockError;
+    type Transport = MockTransport;
+
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        let mut state = self.state();
+        state.creates += 1;
+        let attempt = state.creates;
+        match state.create_script.fault(attempt) {
+            Some(fault) => Err(MockError {
+                operation: Operation::Create,
+                attempt,
+                fault,
+            }),
+            None => Ok(MockTransport {
+                mock: self.clone(),
+                id: attempt,
+            }),
+        }
+    }
+}
+
+///Transport created by `Mock`
+pub struct MockTransport {
+    mock: Mock,
+    id: usize,
+}
+
+impl MockTransport {
+    #[inline(always)]
+    ///Returns number of `create` attempt, which produced this transport
+    pub fn id(&self) -> usize {
+        self.id
+    }
+}
+
+impl Transport<MockError> for MockTransport {
+    fn write(&mut self, severity: Severity, msg: &str) -> Result<(), MockError> {
+        let mut state = self.mock.state();
+        state.writes += 1;
+        let attempt = state.writes;
+        match state.write_script.fault(attempt) {
+            Some(fault) => Err(MockError {
+                operation: Operation::Write,
+                attempt,
+                fault,
+            }),
+            None => {
+                state.records.push((severity, msg.into()));
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+///Clock which always returns the same time, specified as seconds since UNIX epoch
+///
+///```rust
+///use syslog_client::{Facility, Syslog};
+///use syslog_client::syslog::header::{Hostname, Tag};
+///use syslog_client::testing::FixedClock;
+///
+///let syslog = Syslog::new(Facility::LOG_USER, Hostname::new("localhost").unwrap(), Tag::new("test").unwrap()).with_clock(&FixedClock(1_735_689_599));
+///```
+pub struct FixedClock(pub u64);
+
+impl Clock for FixedClock {
+    #[inline(always)]
+    fn now(&self) -> Timestamp {
+        Timestamp::from_unix(self.0, None)
+    }
+}
@@ -2,7 +2,11 @@
 
 use core::fmt;
 
-use crate::{writer, Syslog, Severity, Writer, Rfc3164Buffer, Rfc3164RecordWriter};
+use crate::{writer, Syslog, Severity, Rfc3164Buffer, Rfc3164RecordWriter, Rfc5424Buffer, Rfc5424RecordWriter};
+use crate::syslog::header::MsgId;
+use crate::syslog::structured_data::{Element, SdId, StructuredData, TruncationPolicy};
+use crate::sanitize::SanitizePolicy;
+use crate::writer::SharedWriter;
 
 use tracing::Level;
 use tracing::Event;
@@ -39,17 +43,19 @@ impl From<Level> for Severity {
 }
 
 ///Tracing layer for syslog
-pub struct Rfc3164Layer<W> {
+///
+///With `std` feature transport `T` is cached between records, otherwise it is created for every record.
+pub struct Rfc3164Layer<W, T = <W as writer::MakeTransport>::Transport> {
     syslog: Syslog,
-    writer: W,
+    writer: SharedWriter<W, T>,
 }
 
-impl<W> Rfc3164Layer<W> {
+impl<W: writer::MakeTransport> Rfc3164Layer<W> {
     ///Creates new instance which requires writer to be Clone-able
     pub const fn new(syslog: Syslog, writer: W) -> Self {
         Self {
             syslog,
-            writer,
+            writer: SharedWriter::new(writer),
         }
     }
 }
@@ -61,7 +67,7 @@ struct Rfc3164EventVisitor<'a, W: writer::MakeTransport> {
 impl<W: writer::MakeTransport> Drop for Rfc3164EventVisitor<'_, W> {
     #[inline(always)]
     fn drop(&mut self) {
-        let _ = self.record.flush_without_clear();
+        let _ = self.record.flush();
     }
 }
 
@@ -349,25 +355,351 @@ impl<C: Collect + for<'a> LookupSpan<'a>, W: writer::MakeTransport + 'static> tr
     fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
         let level = (*event.metadata().level()).into();
 
-        let mut writer = Writer::new(&self.writer);
         let mut buffer = Rfc3164Buffer::new();
-        let record = self.syslog.rfc3164_record(&mut writer, &mut buffer, level);
-        let mut visitor = Rfc3164EventVisitor {
-            record,
+        self.writer.with(|writer| {
+            let record = self.syslog.rfc3164_record(writer, &mut buffer, level);
+            let mut visitor = Rfc3164EventVisitor {
+                record,
+            };
+            event.record(&mut visitor);
+
+            //Optionally record all spans after main event data
+            //
+            //We prefer to do it as span data can be potentially very big so we want to start splitting
+            //after event itself is written (as it is more likely to be concise
+            #[cfg(feature = "tracing-full")]
+            if let Some(current_span) = _ctx.event_span(event) {
+                for span in current_span.scope() {
+                    if let Some(span) = span.extensions().get::<Rfc3164SpanAttrsAccum>() {
+                        let _ = fmt::Write::write_fmt(&mut visitor.record, format_args!(" {span}"));
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+///Source of RFC 5424 MSGID
+pub enum MsgIdSource {
+    ///MSGID is omitted
+    None,
+    ///Event's target is used as MSGID
+    ///
+    ///MSGID is omitted if target is longer than 32 characters
+    Target,
+    ///Value of event's field with specified name is used as MSGID
+    ///
+    ///This field is excluded from structured data
+    Field(&'static str),
+}
+
+///Tracing layer for syslog using RFC 5424 format
+///
+///Event fields are written as single SD-ELEMENT with configured SD-ID.
+///
+///With `tracing-full` feature, every span within event's scope is written as SD-ELEMENT with span's name as SD-ID.
+///Spans with name which is not valid SD-ID are omitted.
+///
+///With `std` feature transport `T` is cached between records, otherwise it is created for every record.
+pub struct Rfc5424Layer<W, T = <W as writer::MakeTransport>::Transport> {
+    syslog: Syslog,
+    writer: SharedWriter<W, T>,
+    sd_id: SdId,
+    msg_id: MsgIdSource,
+    max_value_size: usize,
+    truncation: TruncationPolicy,
+}
+
+impl<W: writer::MakeTransport> Rfc5424Layer<W> {
+    ///Creates new instance, using `sd_id` as SD-ID of event fields element
+    ///
+    ///By default event's target is used as MSGID and each value is limited to 256 bytes, truncated if it exceeds limit
+    pub const fn new(syslog: Syslog, writer: W, sd_id: SdId) -> Self {
+        Self {
+            syslog,
+            writer: SharedWriter::new(writer),
+            sd_id,
+            msg_id: MsgIdSource::Target,
+            max_value_size: 256,
+            truncation: TruncationPolicy::Truncate,
+        }
+    }
+
+    ///Sets source of MSGID
+    ///
+    ///Defaults to `MsgIdSource::Target`
+    pub const fn with_msg_id(mut self, msg_id: MsgIdSource) -> Self {
+        self.msg_id = msg_id;
+        self
+    }
+
+    ///Sets limit on size of each value (after escaping)
+    ///
+    ///Defaults to 256
+    pub const fn with_max_value_size(mut self, max_value_size: usize) -> Self {
+        self.max_value_size = max_value_size;
+        self
+    }
+
+    ///Sets policy to handle values exceeding limit or available space
+    ///
+    ///Defaults to `TruncationPolicy::Truncate`
+    pub const fn with_truncation(mut self, truncation: TruncationPolicy) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    #[inline(always)]
+    fn msg_id_field(&self) -> Option<&'static str> {
+        match self.msg_id {
+            MsgIdSource::Field(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+///Accumulator of span's attributes as RFC 5424 SD-ELEMENT
+pub struct Rfc5424SpanAttrsAccum {
+    id: SdId,
+    structured_data: StructuredData,
+}
+
+impl Rfc5424SpanAttrsAccum {
+    #[inline]
+    ///Creates new span accumulator, returning `None` if span's name is not valid SD-ID
+    pub fn new(name: &'static str) -> Option<Self> {
+        let id = SdId::new(name)?;
+        let mut structured_data = StructuredData::new();
+        //Always fits empty buffer
+        drop(structured_data.element(&id));
+        Some(Self {
+            id,
+            structured_data,
+        })
+    }
+
+    #[inline(always)]
+    ///Returns SD-ID of accumulated SD-ELEMENT
+    pub fn id(&self) -> &SdId {
+        &self.id
+    }
+
+    #[inline(always)]
+    ///Returns accumulated SD-ELEMENT
+    pub fn structured_data(&self) -> &StructuredData {
+        &self.structured_data
+    }
+}
+
+///Visitor which writes fields as params of SD-ELEMENT
+struct Rfc5424FieldsVisitor<'a> {
+    element: Element<'a>,
+    max_value_size: usize,
+    truncation: TruncationPolicy,
+    sanitize: SanitizePolicy,
+    msg_id_field: Option<&'static str>,
+    msg_id: Option<MsgId>,
+}
+
+impl Rfc5424FieldsVisitor<'_> {
+    #[inline(always)]
+    fn record_value(&mut self, field: &Field, value: fmt::Arguments<'_>) {
+        let name = field.name();
+        if name == MESSAGE_FIELD {
+            return;
+        } else if Some(name) == self.msg_id_field {
+            let mut msg_id = str_buf::StrBuf::<{ str_buf::capacity(64) }>::new();
+            if fmt::Write::write_fmt(&mut msg_id, value).is_ok() {
+                self.msg_id = MsgId::new(msg_id.as_str());
+            }
+        } else {
+            self.element.param_fmt_limited(name, format_args!("{}", self.sanitize.display(value)), self.max_value_size, self.truncation);
+        }
+    }
+}
+
+impl Visit for Rfc5424FieldsVisitor<'_> {
+    #[inline(always)]
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_value(field, format_args!("{:?}", value));
+    }
+
+    #[inline(always)]
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record_value(field, format_args!("{value}"));
+    }
+
+    #[inline(always)]
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_value(field, format_args!("{value}"));
+    }
+
+    #[inline(always)]
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record_value(field, format_args!("{value}"));
+    }
+
+    #[inline(always)]
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        self.record_value(field, format_args!("{value}"));
+    }
+
+    #[inline(always)]
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        self.record_value(field, format_args!("{value}"));
+    }
+
+    #[inline(always)]
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record_value(field, format_args!("{value}"));
+    }
+
+    #[inline(always)]
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_value(field, format_args!("{value}"));
+    }
+
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    fn record_error(&mut self, field: &Field, value: &(dyn core::error::Error + 'static)) {
+        self.record_value(field, format_args!("{value}"));
+    }
+}
+
+///Visitor which writes only event's message
+struct Rfc5424MessageVisitor<'a, W: writer::MakeTransport> {
+    record: Rfc5424RecordWriter<'a, W>,
+}
+
+impl<W: writer::MakeTransport> Drop for Rfc5424MessageVisitor<'_, W> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        let _ = self.record.flush();
+    }
+}
+
+impl<W: writer::MakeTransport> Visit for Rfc5424MessageVisitor<'_, W> {
+    #[inline(always)]
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == MESSAGE_FIELD {
+            let _ = fmt::Write::write_fmt(&mut self.record, format_args!("{:?}", value));
+        }
+    }
+
+    #[inline(always)]
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == MESSAGE_FIELD {
+            let _ = self.record.write_str(value);
+        }
+    }
+}
+
+impl<C: Collect + for<'a> LookupSpan<'a>, W: writer::MakeTransport + 'static> tracing_subscriber::layer::Layer<C> for Rfc5424Layer<W> {
+    #[inline(always)]
+    fn on_new_span(&self, _attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, C>) {
+        #[cfg(feature = "tracing-full")]
+        {
+            let span = get_span!(_ctx[_id]);
+            let mut extensions = span.extensions_mut();
+            if extensions.get_mut::<Rfc5424SpanAttrsAccum>().is_none() {
+                let mut accum = match Rfc5424SpanAttrsAccum::new(span.name()) {
+                    Some(accum) => accum,
+                    None => return,
+                };
+
+                if let Some(element) = accum.structured_data.last_element() {
+                    let mut visitor = Rfc5424FieldsVisitor {
+                        element,
+                        max_value_size: self.max_value_size,
+                        truncation: self.truncation,
+                        sanitize: self.syslog.sanitize,
+                        msg_id_field: None,
+                        msg_id: None,
+                    };
+                    _attrs.record(&mut visitor);
+                }
+                extensions.insert(accum);
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn on_record(&self, _id: &Id, _values: &Record<'_>, _ctx: Context<'_, C>) {
+        #[cfg(feature = "tracing-full")]
+        {
+            let span = get_span!(_ctx[_id]);
+            let mut extensions = span.extensions_mut();
+            if let Some(accum) = extensions.get_mut::<Rfc5424SpanAttrsAccum>() {
+                if let Some(element) = accum.structured_data.last_element() {
+                    let mut visitor = Rfc5424FieldsVisitor {
+                        element,
+                        max_value_size: self.max_value_size,
+                        truncation: self.truncation,
+                        sanitize: self.syslog.sanitize,
+                        msg_id_field: None,
+                        msg_id: None,
+                    };
+                    _values.record(&mut visitor);
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, C>) {
+        let metadata = event.metadata();
+        let level = (*metadata.level()).into();
+        let msg_id_field = self.msg_id_field();
+
+        let mut msg_id = match self.msg_id {
+            MsgIdSource::Target => MsgId::new(metadata.target()),
+            _ => None,
         };
-        event.record(&mut visitor);
+        let mut structured_data = StructuredData::new();
+        let has_fields = metadata.fields().iter().any(|field| field.name() != MESSAGE_FIELD && Some(field.name()) != msg_id_field);
+        if has_fields || msg_id_field.is_some() {
+            if let Some(element) = structured_data.element(&self.sd_id) {
+                let mut visitor = Rfc5424FieldsVisitor {
+                    element,
+                    max_value_size: self.max_value_size,
+                    truncation: self.truncation,
+                    sanitize: self.syslog.sanitize,
+                    msg_id_field,
+                    msg_id: None,
+                };
+                event.record(&mut visitor);
+                if msg_id_field.is_some() {
+                    msg_id = visitor.msg_id.take();
+                }
+            }
 
-        //Optionally record all spans after main event data
-        //
-        //We prefer to do it as span data can be potentially very big so we want to start splitting
-        //after event itself is written (as it is more likely to be concise
+            //Field used as MSGID should not leave empty element behind
+            if !has_fields {
+                structured_data.clear();
+            }
+        }
+
+        //Optionally record all spans after event data
         #[cfg(feature = "tracing-full")]
         if let Some(current_span) = _ctx.event_span(event) {
             for span in current_span.scope() {
-                if let Some(span) = span.extensions().get::<Rfc3164SpanAttrsAccum>() {
-                    let _ = fmt::Write::write_fmt(&mut visitor.record, format_args!(" {span}"));
+                if let Some(span) = span.extensions().get::<Rfc5424SpanAttrsAccum>() {
+                    //SD-ID must be unique within record, so inner-most element takes precedence
+                    if !structured_data.contains(span.id()) {
+                        structured_data.extend(span.structured_data());
+                    }
                 }
             }
         }
+
+        let mut buffer = Rfc5424Buffer::new();
+        self.writer.with(|writer| {
+            let record = self.syslog.rfc5424_record(writer, &mut buffer, level, msg_id.as_ref(), Some(&structured_data));
+            let mut visitor = Rfc5424MessageVisitor {
+                record,
+            };
+            event.record(&mut visitor);
+        })
     }
 }
@@ -0,0 +1,274 @@
+extern crate std;
+extern crate alloc;
+
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::sync::Arc;
+use std::{io, thread};
+use std::sync::{Condvar, Mutex, MutexGuard};
+
+use super::{MakeTransport, Transport, TransportError, Writer};
+use crate::syslog::Severity;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Policy to handle record when queue is full
+pub enum Overflow {
+    ///Caller is blocked until there is space in queue
+    Block,
+    ///New record is dropped
+    DropNewest,
+    ///Oldest record in queue is dropped to make space for new one
+    DropOldest,
+}
+
+impl Default for Overflow {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::Block
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+///Background worker configuration
+pub struct BackgroundConfig {
+    ///Max number of records within queue
+    ///
+    ///Zero is treated as 1
+    pub capacity: usize,
+    ///Policy to apply when queue is full
+    pub overflow: Overflow,
+    ///Number of attempts to re-try write within worker, same as `Syslog::with_retry_count`
+    pub retry_count: u8,
+}
+
+impl Default for BackgroundConfig {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow: Overflow::Block,
+            retry_count: 2,
+        }
+    }
+}
+
+#[derive(Debug)]
+///Error indicating background worker no longer runs
+pub struct BackgroundError;
+
+impl TransportError for BackgroundError {
+    #[inline(always)]
+    fn is_terminal(&self) -> bool {
+        true
+    }
+}
+
+impl fmt::Display for BackgroundError {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("Background worker is stopped")
+    }
+}
+
+impl std::error::Error for BackgroundError {
+}
+
+struct State {
+    records: VecDeque<(Severity, String)>,
+    is_writing: bool,
+    is_closed: bool,
+}
+
+struct Queue {
+    state: Mutex<State>,
+    //Signals new record or close
+    on_push: Condvar,
+    //Signals progress of worker
+    on_pop: Condvar,
+    capacity: usize,
+    overflow: Overflow,
+    dropped: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl Queue {
+    #[inline(always)]
+    fn state(&self) -> MutexGuard<'_, State> {
+        //State is always consistent between operations, so poisoning can be ignored
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    #[inline(always)]
+    fn wait<'a>(condvar: &Condvar, state: MutexGuard<'a, State>) -> MutexGuard<'a, State> {
+        match condvar.wait(state) {
+            Ok(state) => state,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    fn push(&self, severity: Severity, msg: &str) -> Result<(), BackgroundError> {
+        let mut state = self.state();
+        while !state.is_closed && state.records.len() >= self.capacity {
+            match self.overflow {
+                Overflow::Block => state = Self::wait(&self.on_pop, state),
+                Overflow::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return Ok(());
+                },
+                Overflow::DropOldest => {
+                    state.records.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        if state.is_closed {
+            return Err(BackgroundError);
+        }
+
+        state.records.push_back((severity, msg.into()));
+        drop(state);
+        self.on_push.notify_one();
+        Ok(())
+    }
+
+    //Returns next record, marking worker as busy, or None once queue is closed and empty
+    fn pop(&self) -> Option<(Severity, String)> {
+        let mut state = self.state();
+        state.is_writing = false;
+        self.on_pop.notify_all();
+        loop {
+            if let Some(record) = state.records.pop_front() {
+                state.is_writing = true;
+                return Some(record);
+            } else if state.is_closed {
+                return None;
+            }
+            state = Self::wait(&self.on_push, state);
+        }
+    }
+
+    fn close(&self) {
+        let mut state = self.state();
+        state.is_closed = true;
+        state.is_writing = false;
+        drop(state);
+        self.on_push.notify_all();
+        self.on_pop.notify_all();
+    }
+}
+
+//Closes queue when worker exits, even if it panics, so that nobody waits for it
+struct WorkerGuard(Arc<Queue>);
+
+impl Drop for WorkerGuard {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+fn run<IO: MakeTransport>(queue: Arc<Queue>, transport: IO, retry_count: u8) {
+    let guard = WorkerGuard(queue);
+    let mut writer = Writer::new(transport);
+    while let Some((severity, msg)) = guard.0.pop() {
+        if writer.write_buffer(&msg, severity, retry_count).is_err() {
+            guard.0.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+///Transport wrapper which writes records on dedicated thread
+///
+///Records are encoded on caller's thread and sent to worker through bounded queue, which handles retries.
+///
+///On Drop all queued records are written before worker is stopped.
+pub struct Background {
+    queue: Arc<Queue>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+impl Background {
+    ///Starts worker thread which writes records using `transport`
+    pub fn spawn<IO: MakeTransport + Send + 'static>(transport: IO, config: BackgroundConfig) -> io::Result<Self> {
+        let queue = Arc::new(Queue {
+            state: Mutex::new(State {
+                records: VecDeque::new(),
+                is_writing: false,
+                is_closed: false,
+            }),
+            on_push: Condvar::new(),
+            on_pop: Condvar::new(),
+            capacity: core::cmp::max(config.capacity, 1),
+            overflow: config.overflow,
+            dropped: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+        });
+
+        let worker_queue = queue.clone();
+        let worker = thread::Builder::new().name("syslog".into()).spawn(move || run(worker_queue, transport, config.retry_count))?;
+        Ok(Self {
+            queue,
+            worker: Some(worker),
+        })
+    }
+
+    #[inline(always)]
+    ///Returns number of records dropped due to queue overflow
+    pub fn dropped(&self) -> usize {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    ///Returns number of records which worker failed to write
+    pub fn failed(&self) -> usize {
+        self.queue.failed.load(Ordering::Relaxed)
+    }
+
+    ///Blocks until all queued records are written
+    pub fn flush(&self) {
+        let mut state = self.queue.state();
+        while !state.is_closed && (state.is_writing || !state.records.is_empty()) {
+            state = Queue::wait(&self.queue.on_pop, state);
+        }
+    }
+}
+
+impl MakeTransport for Background {
+    type Error = BackgroundError;
+    type Transport = BackgroundSender;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(BackgroundSender {
+            queue: self.queue.clone(),
+        })
+    }
+}
+
+impl Drop for Background {
+    fn drop(&mut self) {
+        self.flush();
+        self.queue.close();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+///Sender of records to background worker
+pub struct BackgroundSender {
+    queue: Arc<Queue>,
+}
+
+impl Transport<BackgroundError> for BackgroundSender {
+    #[inline(always)]
+    fn write(&mut self, severity: Severity, msg: &str) -> Result<(), BackgroundError> {
+        self.queue.push(severity, msg)
+    }
+}
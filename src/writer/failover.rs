@@ -0,0 +1,183 @@
+use core::{fmt, ops};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use super::{Inner, MakeTransport, Transport, TransportError, Writer};
+use crate::syslog::Severity;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Target of the failover transport
+pub enum Target {
+    ///Primary transport
+    Primary,
+    ///Secondary transport
+    Secondary,
+}
+
+#[derive(Debug)]
+///Error indicating that both targets failed to write
+pub struct FailoverError<A, B> {
+    ///Error of primary target, if it was attempted
+    pub primary: Option<A>,
+    ///Error of secondary target
+    pub secondary: B,
+}
+
+impl<A: TransportError, B: TransportError> TransportError for FailoverError<A, B> {
+    #[inline(always)]
+    fn is_terminal(&self) -> bool {
+        self.secondary.is_terminal()
+    }
+}
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for FailoverError<A, B> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.primary {
+            Some(primary) => fmt.write_fmt(format_args!("Primary failed: {primary}; Secondary failed: {}", self.secondary)),
+            None => fmt.write_fmt(format_args!("Secondary failed: {}", self.secondary)),
+        }
+    }
+}
+
+///Transport which writes to `primary` target, switching to `secondary` once `primary` fails.
+///
+///Target is considered failed when its single attempt to write record fails, while retries are driven by `Syslog` retry count.
+///
+///While on `secondary`, `primary` is probed with single attempt every `probe_interval` records, switching back once it succeeds.
+///
+///Active target is shared by every logger using the same instance.
+///
+///Multiple targets can be chained by using other `Failover` as `secondary`.
+///
+///Circuit breaker of logger's `RetryPolicy` drops records before `Failover` is used, so it should be configured with this in mind (see `RetryPolicy::with_circuit_breaker`).
+pub struct Failover<A, B> {
+    primary: A,
+    secondary: B,
+    probe_interval: usize,
+    is_secondary: AtomicBool,
+    since_switch: AtomicUsize,
+}
+
+impl<A: MakeTransport, B: MakeTransport> Failover<A, B> {
+    #[inline(always)]
+    ///Creates new instance starting with `primary` target
+    pub const fn new(primary: A, secondary: B) -> Self {
+        Self {
+            primary,
+            secondary,
+            probe_interval: 64,
+            is_secondary: AtomicBool::new(false),
+            since_switch: AtomicUsize::new(0),
+        }
+    }
+
+    #[inline(always)]
+    ///Changes number of records written to `secondary` before `primary` is probed.
+    ///
+    ///Zero is treated as 1.
+    ///
+    ///Defaults to 64
+    pub const fn with_probe_interval(mut self, probe_interval: usize) -> Self {
+        self.probe_interval = probe_interval;
+        self
+    }
+
+    #[inline(always)]
+    ///Returns currently active target
+    pub fn active(&self) -> Target {
+        if self.is_secondary.load(Ordering::Acquire) {
+            Target::Secondary
+        } else {
+            Target::Primary
+        }
+    }
+
+    #[inline(always)]
+    fn switch(&self, target: Target) {
+        self.since_switch.store(0, Ordering::Relaxed);
+        self.is_secondary.store(target == Target::Secondary, Ordering::Release);
+    }
+}
+
+impl<'a, A: MakeTransport, B: MakeTransport> MakeTransport for &'a Failover<A, B> {
+    type Error = FailoverError<A::Error, B::Error>;
+    type Transport = FailoverTransport<&'a Failover<A, B>, A, B>;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(FailoverTransport::new(*self))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A: MakeTransport, B: MakeTransport> MakeTransport for crate::std::sync::Arc<Failover<A, B>> {
+    type Error = FailoverError<A::Error, B::Error>;
+    type Transport = FailoverTransport<crate::std::sync::Arc<Failover<A, B>>, A, B>;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(FailoverTransport::new(self.clone()))
+    }
+}
+
+///Failover transport instance
+pub struct FailoverTransport<P: ops::Deref<Target = Failover<A, B>>, A: MakeTransport, B: MakeTransport> {
+    failover: P,
+    primary: Writer<Inner<P, A>>,
+    secondary: Writer<Inner<P, B>>,
+}
+
+impl<P: ops::Deref<Target = Failover<A, B>> + Clone, A: MakeTransport, B: MakeTransport> FailoverTransport<P, A, B> {
+    #[inline(always)]
+    fn new(failover: P) -> Self {
+        //Targets are created on demand
+        Self {
+            primary: Writer::new(Inner::new(failover.clone(), |failover| &failover.primary)),
+            secondary: Writer::new(Inner::new(failover.clone(), |failover| &failover.secondary)),
+            failover,
+        }
+    }
+}
+
+impl<P: ops::Deref<Target = Failover<A, B>>, A: MakeTransport, B: MakeTransport> FailoverTransport<P, A, B> {
+    #[inline(always)]
+    fn write_secondary(&mut self, severity: Severity, msg: &str, primary: Option<A::Error>) -> Result<(), FailoverError<A::Error, B::Error>> {
+        match self.secondary.write_buffer(msg, severity, 0) {
+            Ok(()) => Ok(()),
+            Err(secondary) => Err(FailoverError {
+                primary,
+                secondary,
+            }),
+        }
+    }
+}
+
+impl<P: ops::Deref<Target = Failover<A, B>>, A: MakeTransport, B: MakeTransport> Transport<FailoverError<A::Error, B::Error>> for FailoverTransport<P, A, B> {
+    fn write(&mut self, severity: Severity, msg: &str) -> Result<(), FailoverError<A::Error, B::Error>> {
+        let failover = &*self.failover;
+        match failover.active() {
+            Target::Primary => match self.primary.write_buffer(msg, severity, 0) {
+                Ok(()) => Ok(()),
+                Err(error) => {
+                    failover.switch(Target::Secondary);
+                    self.write_secondary(severity, msg, Some(error))
+                }
+            },
+            Target::Secondary => {
+                let since_switch = failover.since_switch.fetch_add(1, Ordering::Relaxed).saturating_add(1);
+                if since_switch >= failover.probe_interval {
+                    failover.since_switch.store(0, Ordering::Relaxed);
+                    match self.primary.write_buffer(msg, severity, 0) {
+                        Ok(()) => {
+                            failover.switch(Target::Primary);
+                            return Ok(());
+                        },
+                        Err(error) => return self.write_secondary(severity, msg, Some(error)),
+                    }
+                }
+
+                self.write_secondary(severity, msg, None)
+            }
+        }
+    }
+}
@@ -1,14 +1,42 @@
 //!Logger writer
-use core::fmt;
+//!
+//!Combinators of transports (e.g. `Failover`) are shared by every logger using them, so `MakeTransport` is implemented for reference to combinator (e.g. `syslog.rfc3164(&failover)`)
+//!and, with `std` feature, for `Arc` (e.g. to be owned by `tracing` layer).
+use core::{fmt, ops};
 
 use crate::syslog::Severity;
 
+mod retry;
+mod failover;
+mod tee;
+mod route;
 #[cfg(feature = "std")]
 mod std;
+#[cfg(feature = "std")]
+mod background;
+#[cfg(feature = "std")]
+mod spool;
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(feature = "tokio")]
+mod tokio;
+pub use retry::*;
+
 ///Builtin transports
 pub mod transport {
+    pub use super::failover::*;
+    pub use super::tee::*;
+    pub use super::route::*;
     #[cfg(feature = "std")]
     pub use super::std::*;
+    #[cfg(feature = "std")]
+    pub use super::background::*;
+    #[cfg(feature = "std")]
+    pub use super::spool::*;
+    #[cfg(feature = "tls")]
+    pub use super::tls::*;
+    #[cfg(feature = "tokio")]
+    pub use super::tokio::*;
 }
 
 ///Transport builder trait
@@ -50,6 +78,129 @@ impl<IO: MakeTransport> MakeTransport for &'_ IO {
     }
 }
 
+///Builder of transport owned by combinator `P` (e.g. reference or `Arc` to `Failover`)
+pub(crate) struct Inner<P: ops::Deref, IO> {
+    owner: P,
+    get: fn(&P::Target) -> &IO,
+}
+
+impl<P: ops::Deref, IO> Inner<P, IO> {
+    #[inline(always)]
+    pub(crate) const fn new(owner: P, get: fn(&P::Target) -> &IO) -> Self {
+        Self {
+            owner,
+            get,
+        }
+    }
+}
+
+impl<P: ops::Deref, IO: MakeTransport> MakeTransport for Inner<P, IO> {
+    type Error = IO::Error;
+    type Transport = IO::Transport;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        (self.get)(&self.owner).create()
+    }
+}
+
+#[cfg(feature = "tokio")]
+///Async transport builder trait
+pub trait AsyncMakeTransport {
+    ///Internal Error Type
+    type Error: TransportError;
+    ///Transport type
+    type Transport: AsyncTransport<Self::Error>;
+
+    ///Creates instance
+    ///
+    ///This function is called when required or previous instance is no longer able to write
+    fn create(&self) -> impl core::future::Future<Output = Result<Self::Transport, Self::Error>> + Send;
+}
+
+#[cfg(feature = "tokio")]
+///Async log writer
+pub trait AsyncTransport<ERR: TransportError> {
+    ///Performs write of the full encoded message.
+    ///
+    ///Severity is encoded already and is only for informational purpose
+    fn write(&mut self, severity: Severity, msg: &str) -> impl core::future::Future<Output = Result<(), ERR>> + Send;
+}
+
+#[cfg(feature = "tokio")]
+impl<IO: AsyncMakeTransport + Sync> AsyncMakeTransport for &'_ IO {
+    type Error = IO::Error;
+    type Transport = IO::Transport;
+
+    #[inline(always)]
+    fn create(&self) -> impl core::future::Future<Output = Result<Self::Transport, Self::Error>> + Send {
+        AsyncMakeTransport::create(*self)
+    }
+}
+
+//Next step of write loop after failed attempt
+enum Retry<E> {
+    //Give up with error
+    Fail(E),
+    //Retry using new transport
+    Recreate,
+    //Retry using the same transport
+    Reuse,
+}
+
+//State of write loop, shared by sync and async writers
+struct Attempts {
+    //Attempts left, including current one
+    retry_attempts: u8,
+    attempt: u32,
+}
+
+impl Attempts {
+    #[inline(always)]
+    const fn new(retry_count: u8) -> Self {
+        //We will try once + retry_count
+        Self {
+            retry_attempts: retry_count.saturating_add(1),
+            attempt: 0,
+        }
+    }
+
+    //Starts next attempt, returning number of retry to delay, if it is not the first attempt
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        let retry = self.attempt;
+        self.attempt += 1;
+        self.retry_attempts = self.retry_attempts.saturating_sub(1);
+        if retry > 0 {
+            Some(retry)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn on_create_error<E: TransportError>(&self, error: E) -> Retry<E> {
+        //If interface error indicates you cannot proceed then give up immediately
+        if error.is_terminal() || self.retry_attempts == 0 {
+            Retry::Fail(error)
+        } else {
+            Retry::Recreate
+        }
+    }
+
+    #[inline]
+    fn on_write_error<E: TransportError>(&self, error: E) -> Retry<E> {
+        if self.retry_attempts == 0 {
+            Retry::Fail(error)
+        //There is high risk in caching interface that errors out, so re-create it on next attempt
+        } else if error.is_terminal() {
+            Retry::Recreate
+        } else {
+            Retry::Reuse
+        }
+    }
+}
+
 pub(crate) struct Writer<IO: MakeTransport> {
     transport: IO,
     cached_writer: Option<IO::Transport>,
@@ -64,21 +215,38 @@ impl<IO: MakeTransport> Writer<IO> {
         }
     }
 
+    #[inline(always)]
     pub(crate) fn write_buffer(&mut self, buffer: &str, severity: Severity, retry_count: u8) -> Result<(), IO::Error> {
-        //We will try once + retry_count
-        let mut retry_attempts = retry_count.saturating_add(1);
+        self.write_buffer_with(buffer, severity, retry_count, &RetryPolicy::new())
+    }
+
+    pub(crate) fn write_buffer_with(&mut self, buffer: &str, severity: Severity, retry_count: u8, policy: &RetryPolicy) -> Result<(), IO::Error> {
+        let pass = policy.try_pass();
+        if pass == Pass::Drop {
+            return Ok(());
+        }
+
+        let result = self.write_attempts(buffer, severity, retry_count, policy);
+        policy.complete(pass, result.is_ok());
+        result
+    }
+
+    fn write_attempts(&mut self, buffer: &str, severity: Severity, retry_count: u8, policy: &RetryPolicy) -> Result<(), IO::Error> {
+        let mut attempts = Attempts::new(retry_count);
 
         loop {
-            retry_attempts = retry_attempts.saturating_sub(1);
+            if let Some(retry) = attempts.next() {
+                policy.sleep(retry);
+            }
 
             let mut writer = match self.cached_writer.take() {
                 Some(writer) => writer,
                 None => match self.transport.create() {
                     Ok(writer) => writer,
-                    //If interface error indicates you cannot proceed then give up immediately
-                    Err(error) if error.is_terminal() => return Err(error),
-                    Err(_) if retry_attempts > 0 => continue,
-                    Err(error) => break Err(error),
+                    Err(error) => match attempts.on_create_error(error) {
+                        Retry::Fail(error) => break Err(error),
+                        Retry::Recreate | Retry::Reuse => continue,
+                    },
                 },
             };
 
@@ -88,14 +256,138 @@ impl<IO: MakeTransport> Writer<IO> {
                     self.cached_writer = Some(writer);
                     break Ok(());
                 }
-                //If interface error indicates you cannot proceed then give up immediately
-                //Also there is high risk in caching interface that errors out, so avoid that
-                Err(error) if error.is_terminal() => continue,
-                Err(_) if retry_attempts > 0 => {
+                Err(error) => match attempts.on_write_error(error) {
+                    Retry::Fail(error) => break Err(error),
+                    Retry::Recreate => continue,
+                    Retry::Reuse => self.cached_writer = Some(writer),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub(crate) struct AsyncWriter<IO: AsyncMakeTransport> {
+    transport: IO,
+    cached_writer: Option<IO::Transport>,
+}
+
+#[cfg(feature = "tokio")]
+impl<IO: AsyncMakeTransport> AsyncWriter<IO> {
+    #[inline(always)]
+    pub(crate) const fn new(transport: IO) -> Self {
+        Self {
+            transport,
+            cached_writer: None,
+        }
+    }
+
+    //Same as Writer::write_buffer_with, except delays are awaited using tokio timer
+    pub(crate) async fn write_buffer(&mut self, buffer: &str, severity: Severity, retry_count: u8, policy: &RetryPolicy) -> Result<(), IO::Error> {
+        let pass = policy.try_pass();
+        if pass == Pass::Drop {
+            return Ok(());
+        }
+
+        let result = self.write_attempts(buffer, severity, retry_count, policy).await;
+        policy.complete(pass, result.is_ok());
+        result
+    }
+
+    async fn write_attempts(&mut self, buffer: &str, severity: Severity, retry_count: u8, policy: &RetryPolicy) -> Result<(), IO::Error> {
+        let mut attempts = Attempts::new(retry_count);
+
+        loop {
+            if let Some(delay) = attempts.next().and_then(|retry| policy.delay(retry)) {
+                ::tokio::time::sleep(delay).await;
+            }
+
+            let mut writer = match self.cached_writer.take() {
+                Some(writer) => writer,
+                None => match self.transport.create().await {
+                    Ok(writer) => writer,
+                    Err(error) => match attempts.on_create_error(error) {
+                        Retry::Fail(error) => break Err(error),
+                        Retry::Recreate | Retry::Reuse => continue,
+                    },
+                },
+            };
+
+            match writer.write(severity, buffer).await {
+                Ok(()) => {
+                    //Only cache writer, if it is able to write
                     self.cached_writer = Some(writer);
+                    break Ok(());
+                }
+                Err(error) => match attempts.on_write_error(error) {
+                    Retry::Fail(error) => break Err(error),
+                    Retry::Recreate => continue,
+                    Retry::Reuse => self.cached_writer = Some(writer),
                 },
-                Err(error) => break Err(error),
             }
         }
     }
 }
+
+///Transport cache which can be shared between threads
+///
+///Cached transport is taken out for duration of single record, so that concurrent records never block each other (each creating own transport instead).
+///
+///On completion transport is returned to cache, unless other record already did it.
+///
+///Without `std` feature there is no cache and transport is created for every record.
+///
+///Transport type `T` is parameter, so that owners of cache need no bounds on their definition.
+#[cfg(any(feature = "log04", feature = "tracing"))]
+pub(crate) struct SharedWriter<IO, T> {
+    transport: IO,
+    #[cfg(feature = "std")]
+    cached_writer: crate::std::sync::Mutex<Option<T>>,
+    #[cfg(not(feature = "std"))]
+    cached_writer: core::marker::PhantomData<fn() -> T>,
+}
+
+#[cfg(any(feature = "log04", feature = "tracing"))]
+impl<IO: MakeTransport> SharedWriter<IO, IO::Transport> {
+    #[inline(always)]
+    pub(crate) const fn new(transport: IO) -> Self {
+        Self {
+            transport,
+            #[cfg(feature = "std")]
+            cached_writer: crate::std::sync::Mutex::new(None),
+            #[cfg(not(feature = "std"))]
+            cached_writer: core::marker::PhantomData,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    fn cache(&self) -> crate::std::sync::MutexGuard<'_, Option<IO::Transport>> {
+        //Cache only holds transport, so poisoning can be ignored
+        match self.cached_writer.lock() {
+            Ok(cache) => cache,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    ///Runs `cb` with writer using cached transport, if any
+    pub(crate) fn with<R>(&self, cb: impl FnOnce(&mut Writer<&IO>) -> R) -> R {
+        let mut writer = Writer::new(&self.transport);
+        #[cfg(feature = "std")]
+        {
+            writer.cached_writer = self.cache().take();
+        }
+
+        let result = cb(&mut writer);
+
+        #[cfg(feature = "std")]
+        if let Some(transport) = writer.cached_writer.take() {
+            let mut cache = self.cache();
+            if cache.is_none() {
+                *cache = Some(transport);
+            }
+        }
+
+        result
+    }
+}
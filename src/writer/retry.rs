@@ -0,0 +1,288 @@
+use core::time;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+///Monotonic source of time for retry policy
+pub trait Timer {
+    ///Returns time elapsed since arbitrary fixed point
+    fn elapsed(&self) -> time::Duration;
+    ///Blocks current thread for `duration`
+    fn sleep(&self, duration: time::Duration);
+}
+
+///Timer which never advances and never sleeps
+///
+///Backoff has no effect with this timer, while circuit breaker never closes once opened.
+pub struct NoTimer;
+
+impl Timer for NoTimer {
+    #[inline(always)]
+    fn elapsed(&self) -> time::Duration {
+        time::Duration::ZERO
+    }
+
+    #[inline(always)]
+    fn sleep(&self, _: time::Duration) {
+    }
+}
+
+#[cfg(feature = "std")]
+///Timer using `std::time::Instant` and `std::thread::sleep`
+pub struct SystemTimer;
+
+#[cfg(feature = "std")]
+impl Timer for SystemTimer {
+    #[inline]
+    fn elapsed(&self) -> time::Duration {
+        static START: crate::std::sync::OnceLock<crate::std::time::Instant> = crate::std::sync::OnceLock::new();
+        START.get_or_init(crate::std::time::Instant::now).elapsed()
+    }
+
+    #[inline(always)]
+    fn sleep(&self, duration: time::Duration) {
+        crate::std::thread::sleep(duration)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+///Exponential backoff between write attempts
+pub struct Backoff {
+    ///Delay before first retry, doubled for every next retry
+    pub initial: time::Duration,
+    ///Upper limit of delay
+    pub max: time::Duration,
+    ///Whether to randomize delay within upper half of its value
+    ///
+    ///Prevents multiple processes from retrying in lockstep
+    pub jitter: bool,
+}
+
+impl Backoff {
+    ///Returns delay before retry `attempt`, starting from 1
+    ///
+    ///`seed` is only used for jitter.
+    pub fn delay(&self, attempt: u32, seed: u64) -> time::Duration {
+        let shift = core::cmp::min(attempt.saturating_sub(1), 31);
+        let delay = core::cmp::min(self.initial.saturating_mul(1 << shift), self.max);
+        if !self.jitter {
+            return delay;
+        }
+
+        //xorshift is good enough to spread retries
+        let mut random = seed ^ 0x9E37_79B9_7F4A_7C15;
+        random ^= random << 13;
+        random ^= random >> 7;
+        random ^= random << 17;
+
+        let half = delay / 2;
+        let spread = (delay - half).as_nanos() as u64;
+        half + time::Duration::from_nanos(random % spread.saturating_add(1))
+    }
+}
+
+///Circuit breaker state, shared between all writers using it.
+///
+///Once number of consecutive failed records reaches `failure_threshold`, breaker opens and records are dropped without attempting to write, until `cool_down` passes.
+///
+///After `cool_down` single record is attempted as probe, closing breaker on success or opening it again on failure.
+///Records are still dropped while probe is in progress, and if it never completes (e.g. cancelled), next probe is attempted after another `cool_down`.
+///
+///Time is tracked in milliseconds of `Timer::elapsed`, so `cool_down` should be within `usize::MAX / 2` milliseconds.
+pub struct CircuitBreaker {
+    failure_threshold: usize,
+    cool_down: time::Duration,
+    failures: AtomicUsize,
+    is_open: AtomicBool,
+    open_until: AtomicUsize,
+    dropped: AtomicUsize,
+}
+
+impl CircuitBreaker {
+    #[inline(always)]
+    ///Creates new closed instance
+    ///
+    ///Zero `failure_threshold` is treated as 1.
+    pub const fn new(failure_threshold: usize, cool_down: time::Duration) -> Self {
+        Self {
+            failure_threshold: if failure_threshold == 0 { 1 } else { failure_threshold },
+            cool_down,
+            failures: AtomicUsize::new(0),
+            is_open: AtomicBool::new(false),
+            open_until: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    #[inline(always)]
+    ///Returns whether breaker is open
+    ///
+    ///Breaker stays open after `cool_down` until probe succeeds.
+    pub fn is_open(&self) -> bool {
+        self.is_open.load(Ordering::Acquire)
+    }
+
+    #[inline(always)]
+    ///Returns number of records dropped while breaker was open
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    fn millis(now: time::Duration) -> usize {
+        now.as_millis() as usize
+    }
+
+    //Returns whether record should be attempted, dropping it otherwise
+    fn try_pass(&self, now: time::Duration) -> Pass {
+        if !self.is_open() {
+            return Pass::Attempt;
+        }
+
+        let open_until = self.open_until.load(Ordering::Relaxed);
+        //Wrapping difference handles overflow of milliseconds counter
+        let remaining = open_until.wrapping_sub(Self::millis(now)) as isize;
+        if remaining <= 0 {
+            //Only one writer wins the probe, postponing next one until probe is lost
+            let next_probe = Self::millis(now).wrapping_add(Self::millis(self.cool_down));
+            if self.open_until.compare_exchange(open_until, next_probe, Ordering::AcqRel, Ordering::Relaxed).is_ok() {
+                return Pass::Probe;
+            }
+        }
+
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        Pass::Drop
+    }
+
+    fn on_success(&self, pass: Pass) {
+        self.failures.store(0, Ordering::Relaxed);
+        if pass == Pass::Probe {
+            self.is_open.store(false, Ordering::Release);
+        }
+    }
+
+    fn on_failure(&self, pass: Pass, now: time::Duration) {
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed).saturating_add(1);
+        if pass == Pass::Probe || failures >= self.failure_threshold {
+            let open_until = Self::millis(now).wrapping_add(Self::millis(self.cool_down));
+            self.open_until.store(open_until, Ordering::Relaxed);
+            self.is_open.store(true, Ordering::Release);
+        }
+    }
+}
+
+//Outcome of circuit breaker check
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Pass {
+    //Record is dropped
+    Drop,
+    //Record is attempted
+    Attempt,
+    //Record is attempted to probe open breaker
+    Probe,
+}
+
+#[derive(Copy, Clone)]
+///Policy applied by logger when writing records
+///
+///By default retries are attempted immediately and there is no circuit breaker.
+pub struct RetryPolicy {
+    backoff: Option<Backoff>,
+    circuit_breaker: Option<&'static CircuitBreaker>,
+    timer: &'static (dyn Timer + Sync),
+}
+
+impl RetryPolicy {
+    #[inline(always)]
+    ///Creates default policy
+    pub const fn new() -> Self {
+        Self {
+            backoff: None,
+            circuit_breaker: None,
+            #[cfg(feature = "std")]
+            timer: &SystemTimer,
+            #[cfg(not(feature = "std"))]
+            timer: &NoTimer,
+        }
+    }
+
+    #[inline(always)]
+    ///Sets delay between retries
+    pub const fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = Some(backoff);
+        self
+    }
+
+    #[inline(always)]
+    ///Sets circuit breaker to stop writing after repeated failures
+    ///
+    ///While breaker is open, records are dropped and reported as written, so check `CircuitBreaker::dropped` or `RetryPolicy::dropped` to detect loss.
+    ///
+    ///Breaker guards logger's transport as whole, so with `Failover` it only opens once both targets fail,
+    ///and records dropped while it is open never reach `secondary` either.
+    ///Use it only when dropping records is preferred over blocking on unavailable transport.
+    pub const fn with_circuit_breaker(mut self, circuit_breaker: &'static CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    #[inline(always)]
+    ///Sets source of time for backoff and circuit breaker
+    ///
+    ///Defaults to `SystemTimer` with `std` feature, otherwise `NoTimer`
+    pub const fn with_timer(mut self, timer: &'static (dyn Timer + Sync)) -> Self {
+        self.timer = timer;
+        self
+    }
+
+    #[inline]
+    ///Returns number of records dropped by circuit breaker, if any
+    pub fn dropped(&self) -> usize {
+        match self.circuit_breaker {
+            Some(circuit_breaker) => circuit_breaker.dropped(),
+            None => 0,
+        }
+    }
+
+    //Returns whether record should be attempted
+    #[inline]
+    pub(crate) fn try_pass(&self) -> Pass {
+        match self.circuit_breaker {
+            Some(circuit_breaker) => circuit_breaker.try_pass(self.timer.elapsed()),
+            None => Pass::Attempt,
+        }
+    }
+
+    //Returns delay before retry `attempt`
+    #[inline]
+    pub(crate) fn delay(&self, attempt: u32) -> Option<time::Duration> {
+        match self.backoff {
+            Some(backoff) => Some(backoff.delay(attempt, self.timer.elapsed().as_nanos() as u64)),
+            None => None,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn sleep(&self, attempt: u32) {
+        if let Some(delay) = self.delay(attempt) {
+            self.timer.sleep(delay);
+        }
+    }
+
+    //Records outcome of record write
+    #[inline]
+    pub(crate) fn complete(&self, pass: Pass, is_success: bool) {
+        if let Some(circuit_breaker) = self.circuit_breaker {
+            if is_success {
+                circuit_breaker.on_success(pass);
+            } else {
+                circuit_breaker.on_failure(pass, self.timer.elapsed());
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,140 @@
+use core::{fmt, ops};
+
+use super::{Inner, MakeTransport, Transport, TransportError, Writer};
+use crate::syslog::Severity;
+
+#[derive(Debug)]
+///Error of the route used to write record
+pub enum RouterError<A, B> {
+    ///Error of the severity route
+    Route(A),
+    ///Error of the fallback route
+    Fallback(B),
+}
+
+impl<A: TransportError, B: TransportError> TransportError for RouterError<A, B> {
+    #[inline(always)]
+    fn is_terminal(&self) -> bool {
+        match self {
+            Self::Route(error) => error.is_terminal(),
+            Self::Fallback(error) => error.is_terminal(),
+        }
+    }
+}
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for RouterError<A, B> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Route(error) => fmt.write_fmt(format_args!("Route failed: {error}")),
+            Self::Fallback(error) => fmt.write_fmt(format_args!("Fallback failed: {error}")),
+        }
+    }
+}
+
+///Transport which routes records by severity
+///
+///Records are written to the first route, which severity range contains record's severity, while the rest goes to `fallback`.
+///
+///Retries are driven by `Syslog` retry count.
+///
+///Routes of different types can be configured by using other `Router` as `fallback`.
+pub struct Router<A, B, const N: usize> {
+    //Index of route for every severity, `N` for fallback
+    table: [usize; 8],
+    routes: [A; N],
+    fallback: B,
+}
+
+impl<A: MakeTransport, B: MakeTransport, const N: usize> Router<A, B, N> {
+    ///Creates new instance routing severities to corresponding route
+    ///
+    ///Ranges are inclusive and their order doesn't matter (e.g. `Severity::LOG_EMERG..=Severity::LOG_ERR` matches `LOG_ERR` and more important)
+    pub fn new(routes: [(ops::RangeInclusive<Severity>, A); N], fallback: B) -> Self {
+        let mut table = [N; 8];
+        for (idx, (severities, _)) in routes.iter().enumerate().rev() {
+            let start = *severities.start() as usize;
+            let end = *severities.end() as usize;
+            for route in &mut table[core::cmp::min(start, end)..=core::cmp::max(start, end)] {
+                *route = idx;
+            }
+        }
+        Self {
+            table,
+            routes: routes.map(|(_, route)| route),
+            fallback,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns index of route, to which `severity` is written, or `None` if it is written to `fallback`
+    pub const fn route(&self, severity: Severity) -> Option<usize> {
+        match self.table[severity as usize] {
+            idx if idx < N => Some(idx),
+            _ => None,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns whether `severity` is written to any of routes
+    pub const fn is_routed(&self, severity: Severity) -> bool {
+        self.route(severity).is_some()
+    }
+}
+
+impl<'a, A: MakeTransport, B: MakeTransport, const N: usize> MakeTransport for &'a Router<A, B, N> {
+    type Error = RouterError<A::Error, B::Error>;
+    type Transport = RouterTransport<&'a Router<A, B, N>, A, B, N>;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(RouterTransport::new(*self))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A: MakeTransport, B: MakeTransport, const N: usize> MakeTransport for crate::std::sync::Arc<Router<A, B, N>> {
+    type Error = RouterError<A::Error, B::Error>;
+    type Transport = RouterTransport<crate::std::sync::Arc<Router<A, B, N>>, A, B, N>;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(RouterTransport::new(self.clone()))
+    }
+}
+
+///Router transport instance
+pub struct RouterTransport<P: ops::Deref<Target = Router<A, B, N>>, A: MakeTransport, B: MakeTransport, const N: usize> {
+    router: P,
+    //Transports of routes, cached between records
+    routes: [Option<A::Transport>; N],
+    fallback: Writer<Inner<P, B>>,
+}
+
+impl<P: ops::Deref<Target = Router<A, B, N>> + Clone, A: MakeTransport, B: MakeTransport, const N: usize> RouterTransport<P, A, B, N> {
+    #[inline(always)]
+    fn new(router: P) -> Self {
+        //Routes are created on demand
+        Self {
+            routes: core::array::from_fn(|_| None),
+            fallback: Writer::new(Inner::new(router.clone(), |router| &router.fallback)),
+            router,
+        }
+    }
+}
+
+impl<P: ops::Deref<Target = Router<A, B, N>>, A: MakeTransport, B: MakeTransport, const N: usize> Transport<RouterError<A::Error, B::Error>> for RouterTransport<P, A, B, N> {
+    #[inline]
+    fn write(&mut self, severity: Severity, msg: &str) -> Result<(), RouterError<A::Error, B::Error>> {
+        match self.router.route(severity) {
+            Some(idx) => {
+                let mut route = Writer::new(&self.router.routes[idx]);
+                route.cached_writer = self.routes[idx].take();
+                let result = route.write_buffer(msg, severity, 0);
+                self.routes[idx] = route.cached_writer.take();
+                result.map_err(RouterError::Route)
+            },
+            None => self.fallback.write_buffer(msg, severity, 0).map_err(RouterError::Fallback),
+        }
+    }
+}
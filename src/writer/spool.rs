@@ -0,0 +1,477 @@
+extern crate std;
+extern crate alloc;
+
+use core::{fmt, ops, time};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::{fs, io, path};
+use std::io::Write;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use super::{Inner, MakeTransport, Transport, TransportError, Writer};
+use crate::syslog::Severity;
+
+#[derive(Copy, Clone, Debug)]
+///Spool configuration
+pub struct SpoolConfig {
+    ///Max size of spooled records in bytes
+    ///
+    ///Oldest records are discarded to make space for new one.
+    pub max_size: u64,
+    ///Max age of spooled record, after which it is discarded
+    pub max_age: time::Duration,
+}
+
+impl Default for SpoolConfig {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            max_size: 1024 * 1024,
+            max_age: time::Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+#[derive(Debug)]
+///Error indicating that record could be neither written nor spooled
+pub struct SpoolError<E> {
+    ///Error of inner transport
+    pub transport: E,
+    ///Error of spool file
+    pub spool: io::Error,
+}
+
+impl<E: TransportError> TransportError for SpoolError<E> {
+    #[inline(always)]
+    fn is_terminal(&self) -> bool {
+        self.transport.is_terminal()
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for SpoolError<E> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_fmt(format_args!("Transport failed: {}; Spool failed: {}", self.transport, self.spool))
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for SpoolError<E> {
+}
+
+//Spool file starts with offset of the first record that is not replayed yet, so that replay only needs to update it.
+//Offset is written as fixed number of digits to be updated in place.
+const HEAD_DIGITS: usize = 20;
+const HEAD_SIZE: u64 = HEAD_DIGITS as u64 + 1;
+
+#[inline]
+fn digits(mut num: u64) -> u64 {
+    let mut result = 1;
+    while num >= 10 {
+        num /= 10;
+        result += 1;
+    }
+    result
+}
+
+struct Record {
+    //Seconds since UNIX epoch
+    timestamp: u64,
+    severity: Severity,
+    msg: String,
+    //Sequence number to identify record while lock is released
+    seq: u64,
+}
+
+impl Record {
+    #[inline]
+    fn encode(&self, out: &mut Vec<u8>) {
+        //Message may contain new lines, so it is prefixed with its length
+        let _ = writeln!(out, "{} {} {}", self.timestamp, self.severity as u8, self.msg.len());
+        out.extend_from_slice(self.msg.as_bytes());
+        out.push(b'\n');
+    }
+
+    #[inline]
+    fn encoded_len(&self) -> u64 {
+        let msg_len = self.msg.len() as u64;
+        digits(self.timestamp) + 1 + 1 + 1 + digits(msg_len) + 1 + msg_len + 1
+    }
+
+    //Returns decoded record and rest of input, or None if input is corrupted or incomplete
+    fn decode(input: &[u8], seq: u64) -> Option<(Self, &[u8])> {
+        let header_end = input.iter().position(|byte| *byte == b'\n')?;
+        let header = core::str::from_utf8(&input[..header_end]).ok()?;
+        let mut parts = header.split(' ');
+        let timestamp = parts.next()?.parse().ok()?;
+        let severity = match parts.next()?.parse::<u8>().ok()? {
+            severity @ 0..=7 => Severity::from_priority(severity),
+            _ => return None,
+        };
+        let len: usize = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let rest = &input[header_end + 1..];
+        if rest.len() <= len || rest[len] != b'\n' {
+            return None;
+        }
+        let msg = core::str::from_utf8(&rest[..len]).ok()?;
+        Some((Self {
+            timestamp,
+            severity,
+            msg: msg.into(),
+            seq,
+        }, &rest[len + 1..]))
+    }
+}
+
+//Returns offset of the first record, if file starts with it
+fn decode_head(input: &[u8]) -> Option<u64> {
+    let head = input.get(..HEAD_DIGITS)?;
+    if input.get(HEAD_DIGITS) != Some(&b'\n') || !head.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    core::str::from_utf8(head).ok()?.parse().ok()
+}
+
+struct State {
+    records: VecDeque<Record>,
+    //Encoded size of records
+    size: u64,
+    //Offset of the first record within file, zero if file is empty
+    head: u64,
+    next_seq: u64,
+    is_replaying: bool,
+}
+
+impl State {
+    #[inline]
+    fn pop_front(&mut self) -> Option<Record> {
+        let record = self.records.pop_front()?;
+        let len = record.encoded_len();
+        self.size -= len;
+        self.head += len;
+        Some(record)
+    }
+}
+
+#[inline(always)]
+fn unix_now() -> u64 {
+    match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+        Ok(now) => now.as_secs(),
+        Err(_) => 0,
+    }
+}
+
+///Transport wrapper which stores records, that `transport` failed to write, in a local file.
+///
+///Spooled records are replayed in order before next record is written, so records are never re-ordered.
+///While spool is not empty, new record is only written once all spooled records are replayed, otherwise it is spooled too.
+///
+///Spool file is loaded on open, so records survive process restart.
+///Replayed and discarded records stay in file until they outgrow the rest, at which point file is compacted.
+///
+///Record is reported as failed only if it cannot be written to spool file either.
+///Every change of spool file is flushed to disk (`sync_data`), so spooled record survives system crash too.
+///
+///Spool file is shared by every logger using the same instance.
+pub struct Spool<IO> {
+    transport: IO,
+    path: path::PathBuf,
+    config: SpoolConfig,
+    state: Mutex<State>,
+    dropped: AtomicUsize,
+}
+
+impl<IO: MakeTransport> Spool<IO> {
+    ///Opens spool at `path`, creating it if necessary
+    ///
+    ///Corrupted tail of file (e.g. due to crash during write) is discarded along with records exceeding limits.
+    pub fn open(path: impl Into<path::PathBuf>, transport: IO, config: SpoolConfig) -> io::Result<Self> {
+        let path = path.into();
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(error) => return Err(error),
+        };
+
+        let mut input = content.as_slice();
+        if let Some(head) = decode_head(input) {
+            input = content.get(head as usize..).unwrap_or_default();
+        }
+
+        let mut state = State {
+            records: VecDeque::new(),
+            size: 0,
+            head: 0,
+            next_seq: 0,
+            is_replaying: false,
+        };
+        while let Some((record, rest)) = Record::decode(input, state.next_seq) {
+            state.next_seq += 1;
+            state.size += record.encoded_len();
+            state.records.push_back(record);
+            input = rest;
+        }
+
+        let spool = Self {
+            transport,
+            path,
+            config,
+            state: Mutex::new(state),
+            dropped: AtomicUsize::new(0),
+        };
+        {
+            let mut state = spool.state();
+            spool.discard_expired(&mut state);
+            while state.size > spool.config.max_size && state.pop_front().is_some() {
+                spool.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            spool.compact(&mut state)?;
+        }
+        Ok(spool)
+    }
+
+    #[inline(always)]
+    ///Returns number of records in spool
+    pub fn len(&self) -> usize {
+        self.state().records.len()
+    }
+
+    #[inline(always)]
+    ///Returns whether spool has no records
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline(always)]
+    ///Returns number of records discarded due to size or age limits
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    fn state(&self) -> MutexGuard<'_, State> {
+        //State is always consistent between operations, so poisoning can be ignored
+        match self.state.lock() {
+            Ok(state) => state,
+            Err(error) => error.into_inner(),
+        }
+    }
+
+    //Discards expired records from the front, returning whether any is discarded
+    fn discard_expired(&self, state: &mut State) -> bool {
+        let now = unix_now();
+        let max_age = self.config.max_age.as_secs();
+        let len = state.records.len();
+        while state.records.front().map_or(false, |record| now.saturating_sub(record.timestamp) > max_age) {
+            state.pop_front();
+        }
+        let discarded = len - state.records.len();
+        self.dropped.fetch_add(discarded, Ordering::Relaxed);
+        discarded > 0
+    }
+
+    //Replaces file content with records, starting with its head
+    fn compact(&self, state: &mut State) -> io::Result<()> {
+        if state.records.is_empty() {
+            fs::write(&self.path, b"")?;
+            state.head = 0;
+            return Ok(());
+        }
+
+        let mut content = Vec::with_capacity((HEAD_SIZE + state.size) as usize);
+        let _ = writeln!(content, "{:0width$}", HEAD_SIZE, width = HEAD_DIGITS);
+        for record in state.records.iter() {
+            record.encode(&mut content);
+        }
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(&content)?;
+        file.sync_data()?;
+        fs::rename(&tmp, &self.path)?;
+        state.head = HEAD_SIZE;
+        Ok(())
+    }
+
+    //Persists records removed from the front of file
+    fn sync(&self, state: &mut State) -> io::Result<()> {
+        //Compact once removed records outgrow remaining ones, so that file is rewritten only occasionally
+        if state.records.is_empty() || state.head - HEAD_SIZE > state.size {
+            return self.compact(state);
+        }
+
+        let mut file = fs::OpenOptions::new().write(true).open(&self.path)?;
+        writeln!(file, "{:0width$}", state.head, width = HEAD_DIGITS)?;
+        file.sync_data()
+    }
+
+    fn append(&self, state: &mut State, severity: Severity, msg: &str) -> io::Result<()> {
+        let record = Record {
+            timestamp: unix_now(),
+            severity,
+            msg: msg.into(),
+            seq: state.next_seq,
+        };
+        let len = record.encoded_len();
+        if len > self.config.max_size {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        if state.size + len > self.config.max_size {
+            while state.size + len > self.config.max_size && state.pop_front().is_some() {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            self.sync(state)?;
+        }
+
+        let mut content = Vec::with_capacity(HEAD_SIZE as usize + len as usize);
+        if state.head == 0 {
+            let _ = writeln!(content, "{:0width$}", HEAD_SIZE, width = HEAD_DIGITS);
+        }
+        record.encode(&mut content);
+
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(&content)?;
+        file.sync_data()?;
+        if state.head == 0 {
+            state.head = HEAD_SIZE;
+        }
+        state.next_seq += 1;
+        state.size += len;
+        state.records.push_back(record);
+        Ok(())
+    }
+
+    //Returns whether whole spool is replayed, so that new record can be written directly
+    //
+    //Lock is released while record is written, during which other records are spooled
+    fn replay<T: MakeTransport>(&self, writer: &mut Writer<T>) -> bool {
+        let mut state = self.state();
+        if state.is_replaying {
+            return false;
+        }
+        let mut is_changed = self.discard_expired(&mut state);
+        if state.records.is_empty() {
+            return !is_changed || self.sync(&mut state).is_ok();
+        }
+        state.is_replaying = true;
+        drop(state);
+        //Declared before lock, so that lock is released first on unwinding
+        let _guard = ReplayGuard(self);
+        let mut state = self.state();
+
+        let is_replayed = loop {
+            let (seq, severity, msg) = match state.records.front() {
+                Some(record) => (record.seq, record.severity, record.msg.clone()),
+                None => break true,
+            };
+            drop(state);
+            let is_written = writer.write_buffer(&msg, severity, 0).is_ok();
+            state = self.state();
+            if !is_written {
+                break false;
+            }
+            //Record may be discarded to fit size limit meanwhile
+            if state.records.front().map_or(false, |record| record.seq == seq) {
+                state.pop_front();
+                is_changed = true;
+            }
+        };
+
+        //Queue is observed empty under the same lock, so records spooled by others during replay are replayed too
+        state.is_replaying = false;
+        //If spool file is out of sync with replayed records, keep order by spooling record too
+        let is_synced = !is_changed || self.sync(&mut state).is_ok();
+        is_replayed && is_synced
+    }
+}
+
+//Resets replay flag if writer panics, so that spool is not stuck spooling every record
+struct ReplayGuard<'a, IO: MakeTransport>(&'a Spool<IO>);
+
+impl<IO: MakeTransport> Drop for ReplayGuard<'_, IO> {
+    #[inline]
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.0.state().is_replaying = false;
+        }
+    }
+}
+
+impl<'a, IO: MakeTransport> MakeTransport for &'a Spool<IO> {
+    type Error = SpoolError<IO::Error>;
+    type Transport = SpoolTransport<&'a Spool<IO>, IO>;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(SpoolTransport::new(*self))
+    }
+}
+
+impl<IO: MakeTransport> MakeTransport for Arc<Spool<IO>> {
+    type Error = SpoolError<IO::Error>;
+    type Transport = SpoolTransport<Arc<Spool<IO>>, IO>;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(SpoolTransport::new(self.clone()))
+    }
+}
+
+///Spool transport instance
+pub struct SpoolTransport<P: ops::Deref<Target = Spool<IO>>, IO: MakeTransport> {
+    spool: P,
+    writer: Writer<Inner<P, IO>>,
+}
+
+impl<P: ops::Deref<Target = Spool<IO>> + Clone, IO: MakeTransport> SpoolTransport<P, IO> {
+    #[inline(always)]
+    fn new(spool: P) -> Self {
+        //Inner transport is created on demand
+        Self {
+            writer: Writer::new(Inner::new(spool.clone(), |spool| &spool.transport)),
+            spool,
+        }
+    }
+}
+
+impl<P: ops::Deref<Target = Spool<IO>>, IO: MakeTransport> Transport<SpoolError<IO::Error>> for SpoolTransport<P, IO> {
+    fn write(&mut self, severity: Severity, msg: &str) -> Result<(), SpoolError<IO::Error>> {
+        let spool = &*self.spool;
+
+        let transport = if spool.replay(&mut self.writer) {
+            match self.writer.write_buffer(msg, severity, 0) {
+                Ok(()) => return Ok(()),
+                Err(error) => Some(error),
+            }
+        } else {
+            None
+        };
+
+        let result = spool.append(&mut spool.state(), severity, msg);
+        match result {
+            Ok(()) => Ok(()),
+            Err(error) => match transport {
+                Some(transport) => Err(SpoolError {
+                    transport,
+                    spool: error,
+                }),
+                //Record cannot be spooled, so rather write it out of order than lose it
+                None => match self.writer.write_buffer(msg, severity, 0) {
+                    Ok(()) => Ok(()),
+                    Err(transport) => Err(SpoolError {
+                        transport,
+                        spool: error,
+                    }),
+                },
+            },
+        }
+    }
+}
@@ -110,6 +110,77 @@ impl Transport<io::Error> for net::UdpSocket {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Framing of messages within stream transport, as per [RFC 6587](https://datatracker.ietf.org/doc/html/rfc6587#section-3.4)
+pub enum Framing {
+    ///Message is terminated by LF.
+    ///
+    ///Message with embedded LF is going to be interpreted by server as multiple messages.
+    NonTransparent,
+    ///Message is prefixed with its length in bytes followed by space (e.g. `11 hello world`)
+    OctetCounting,
+}
+
+//Length of message as decimal number followed by space
+pub(crate) type FramePrefix = str_buf::StrBuf<{ str_buf::capacity(21) }>;
+
+impl Framing {
+    #[inline]
+    pub(crate) fn encode_prefix(self, msg: &str) -> FramePrefix {
+        let mut prefix = FramePrefix::new();
+        if let Self::OctetCounting = self {
+            let _ = core::fmt::Write::write_fmt(&mut prefix, format_args!("{} ", msg.len()));
+        }
+        prefix
+    }
+
+    #[inline(always)]
+    pub(crate) const fn suffix(self) -> &'static [u8] {
+        match self {
+            Self::NonTransparent => LF,
+            Self::OctetCounting => &[],
+        }
+    }
+
+    ///Writes `msg` using this framing
+    pub fn write(self, out: &mut impl io::Write, msg: &str) -> Result<(), io::Error> {
+        let prefix = self.encode_prefix(msg);
+        let frame = [prefix.as_str().as_bytes(), msg.as_bytes(), self.suffix()];
+        let mut written = 0;
+        loop {
+            let slices = frame_slices(&frame, written);
+            if slices.iter().all(|slice| slice.is_empty()) {
+                break;
+            }
+            match out.write_vectored(&slices) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(size) => written += size,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            }
+        }
+        out.flush()
+    }
+}
+
+//Returns parts of frame, that are not written yet, so that frame is sent with as few writes as possible
+pub(crate) fn frame_slices<'a>(frame: &[&'a [u8]; 3], mut written: usize) -> [io::IoSlice<'a>; 3] {
+    let mut parts = *frame;
+    for part in parts.iter_mut() {
+        let skip = core::cmp::min(written, part.len());
+        *part = &part[skip..];
+        written -= skip;
+    }
+    [io::IoSlice::new(parts[0]), io::IoSlice::new(parts[1]), io::IoSlice::new(parts[2])]
+}
+
+impl Default for Framing {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::NonTransparent
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 ///Tcp transport
 pub struct Tcp {
@@ -119,31 +190,72 @@ pub struct Tcp {
     pub timeout: Option<time::Duration>,
 }
 
+impl Tcp {
+    #[inline(always)]
+    ///Specifies framing of messages, instead of default non-transparent framing
+    pub const fn with_framing(self, framing: Framing) -> FramedTcp {
+        FramedTcp {
+            tcp: self,
+            framing,
+        }
+    }
+}
+
 impl MakeTransport for Tcp {
     type Error = io::Error;
     type Transport = TcpSocket;
 
     #[inline(always)]
     fn create(&self) -> Result<Self::Transport, Self::Error> {
-        let socket = match self.timeout {
-            Some(timeout) => net::TcpStream::connect_timeout(&self.remote_addr, timeout)?,
-            None => net::TcpStream::connect(self.remote_addr)?,
+        self.with_framing(Framing::NonTransparent).create()
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+///Tcp transport using specified framing
+pub struct FramedTcp {
+    ///Tcp transport
+    pub tcp: Tcp,
+    ///Framing of messages
+    pub framing: Framing,
+}
+
+impl MakeTransport for FramedTcp {
+    type Error = io::Error;
+    type Transport = TcpSocket;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        let socket = match self.tcp.timeout {
+            Some(timeout) => net::TcpStream::connect_timeout(&self.tcp.remote_addr, timeout)?,
+            None => net::TcpStream::connect(self.tcp.remote_addr)?,
         };
-        socket.set_write_timeout(self.timeout)?;
-        Ok(TcpSocket(socket))
+        socket.set_write_timeout(self.tcp.timeout)?;
+        Ok(TcpSocket::new(socket, self.framing))
     }
 }
 
-#[repr(transparent)]
 ///TCP Socket wrapper which shutdowns socket on Drop
-pub struct TcpSocket(net::TcpStream);
+pub struct TcpSocket {
+    socket: net::TcpStream,
+    framing: Framing,
+}
+
+impl TcpSocket {
+    #[inline(always)]
+    ///Creates new instance using specified `framing`
+    pub const fn new(socket: net::TcpStream, framing: Framing) -> Self {
+        Self {
+            socket,
+            framing,
+        }
+    }
+}
 
 impl Transport<io::Error> for TcpSocket {
     #[inline(always)]
     fn write(&mut self, _severity: Severity, msg: &str) -> Result<(), io::Error> {
-        io::Write::write_all(&mut self.0, msg.as_bytes())?;
-        io::Write::write_all(&mut self.0, LF)?;
-        io::Write::flush(&mut self.0)
+        self.framing.write(&mut self.socket, msg)
     }
 }
 
@@ -151,21 +263,22 @@ impl ops::Deref for TcpSocket {
     type Target = net::TcpStream;
     #[inline(always)]
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.socket
     }
 }
 
 impl From<net::TcpStream> for TcpSocket {
     #[inline(always)]
+    ///Creates new instance using non-transparent framing
     fn from(socket: net::TcpStream) -> Self {
-        Self(socket)
+        Self::new(socket, Framing::NonTransparent)
     }
 }
 
 impl Drop for TcpSocket {
     #[inline(always)]
     fn drop(&mut self) {
-        let _ = self.0.shutdown(net::Shutdown::Both);
+        let _ = self.socket.shutdown(net::Shutdown::Both);
     }
 }
 
@@ -173,8 +286,8 @@ impl Drop for TcpSocket {
 ///Unix socket writer
 pub struct Unix<'a> {
     #[cfg_attr(not(unix), allow(dead_code))]
-    path: &'a str,
-    timeout: Option<time::Duration>,
+    pub(super) path: &'a str,
+    pub(super) timeout: Option<time::Duration>,
 }
 
 impl Unix<'static> {
@@ -0,0 +1,146 @@
+use core::{fmt, ops};
+
+use super::{Inner, MakeTransport, Transport, TransportError, Writer};
+use crate::syslog::Severity;
+
+#[derive(Debug)]
+///Error indicating that at least one leg of `Tee` failed to write
+pub struct TeeError<A, B> {
+    ///Error of the first leg, if it failed
+    pub first: Option<A>,
+    ///Error of the second leg, if it failed
+    pub second: Option<B>,
+    is_terminal: bool,
+}
+
+impl<A, B> TeeError<A, B> {
+    #[inline(always)]
+    ///Returns whether first leg failed
+    pub const fn is_first_failed(&self) -> bool {
+        self.first.is_some()
+    }
+
+    #[inline(always)]
+    ///Returns whether second leg failed
+    pub const fn is_second_failed(&self) -> bool {
+        self.second.is_some()
+    }
+}
+
+impl<A: TransportError, B: TransportError> TransportError for TeeError<A, B> {
+    #[inline(always)]
+    fn is_terminal(&self) -> bool {
+        self.is_terminal
+    }
+}
+
+impl<A: fmt::Display, B: fmt::Display> fmt::Display for TeeError<A, B> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.first, &self.second) {
+            (Some(first), Some(second)) => fmt.write_fmt(format_args!("First leg failed: {first}; Second leg failed: {second}")),
+            (Some(first), None) => fmt.write_fmt(format_args!("First leg failed: {first}")),
+            (None, Some(second)) => fmt.write_fmt(format_args!("Second leg failed: {second}")),
+            (None, None) => fmt.write_str("No leg failed"),
+        }
+    }
+}
+
+///Transport which writes every record to both `first` and `second` transport
+///
+///Failure of one leg doesn't prevent write to the other one.
+///
+///Retries are driven by `Syslog` retry count, each attempt writing only to legs which have not written record yet.
+///
+///Error is terminal only when failed leg's error is terminal and no leg has written record yet, as otherwise re-created tee would duplicate record on the other leg.
+///
+///Multiple destinations can be chained by using other `Tee` as `second`.
+pub struct Tee<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: MakeTransport, B: MakeTransport> Tee<A, B> {
+    #[inline(always)]
+    ///Creates new instance
+    pub const fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+        }
+    }
+}
+
+impl<'a, A: MakeTransport, B: MakeTransport> MakeTransport for &'a Tee<A, B> {
+    type Error = TeeError<A::Error, B::Error>;
+    type Transport = TeeTransport<&'a Tee<A, B>, A, B>;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(TeeTransport::new(*self))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A: MakeTransport, B: MakeTransport> MakeTransport for crate::std::sync::Arc<Tee<A, B>> {
+    type Error = TeeError<A::Error, B::Error>;
+    type Transport = TeeTransport<crate::std::sync::Arc<Tee<A, B>>, A, B>;
+
+    #[inline(always)]
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(TeeTransport::new(self.clone()))
+    }
+}
+
+///Tee transport instance
+pub struct TeeTransport<P: ops::Deref<Target = Tee<A, B>>, A: MakeTransport, B: MakeTransport> {
+    first: Writer<Inner<P, A>>,
+    second: Writer<Inner<P, B>>,
+    is_first_pending: bool,
+    is_second_pending: bool,
+}
+
+impl<P: ops::Deref<Target = Tee<A, B>> + Clone, A: MakeTransport, B: MakeTransport> TeeTransport<P, A, B> {
+    #[inline(always)]
+    fn new(tee: P) -> Self {
+        //Legs are created on demand
+        Self {
+            first: Writer::new(Inner::new(tee.clone(), |tee| &tee.first)),
+            second: Writer::new(Inner::new(tee, |tee| &tee.second)),
+            is_first_pending: true,
+            is_second_pending: true,
+        }
+    }
+}
+
+impl<P: ops::Deref<Target = Tee<A, B>>, A: MakeTransport, B: MakeTransport> Transport<TeeError<A::Error, B::Error>> for TeeTransport<P, A, B> {
+    fn write(&mut self, severity: Severity, msg: &str) -> Result<(), TeeError<A::Error, B::Error>> {
+        let first = if self.is_first_pending {
+            self.first.write_buffer(msg, severity, 0).err()
+        } else {
+            None
+        };
+        let second = if self.is_second_pending {
+            self.second.write_buffer(msg, severity, 0).err()
+        } else {
+            None
+        };
+
+        if first.is_none() && second.is_none() {
+            //Record is written, prepare for next one
+            self.is_first_pending = true;
+            self.is_second_pending = true;
+            Ok(())
+        } else {
+            let is_written = first.is_none() || second.is_none();
+            let is_terminal = !is_written && (first.as_ref().map_or(false, TransportError::is_terminal) || second.as_ref().map_or(false, TransportError::is_terminal));
+            self.is_first_pending = first.is_some();
+            self.is_second_pending = second.is_some();
+            Err(TeeError {
+                first,
+                second,
+                is_terminal,
+            })
+        }
+    }
+}
@@ -0,0 +1,176 @@
+extern crate std;
+extern crate alloc;
+
+use core::{fmt, time};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::{io, net};
+
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+
+use super::{MakeTransport, Transport, TransportError};
+use super::std::Framing;
+use crate::syslog::Severity;
+
+#[derive(Debug)]
+///TLS transport error
+pub enum TlsError {
+    ///I/O error
+    Io(io::Error),
+    ///TLS error, including handshake and certificate verification failures
+    Tls(rustls::Error),
+}
+
+impl TransportError for TlsError {
+    #[inline(always)]
+    fn is_terminal(&self) -> bool {
+        match self {
+            Self::Io(error) => error.is_terminal(),
+            //Neither handshake nor certificate can be fixed by retrying
+            Self::Tls(_) => true,
+        }
+    }
+}
+
+impl From<io::Error> for TlsError {
+    #[inline]
+    fn from(error: io::Error) -> Self {
+        //rustls reports its errors as io::Error when used via io traits
+        match error.get_ref().and_then(|error| error.downcast_ref::<rustls::Error>()) {
+            Some(error) => Self::Tls(error.clone()),
+            None => Self::Io(error),
+        }
+    }
+}
+
+impl From<rustls::Error> for TlsError {
+    #[inline(always)]
+    fn from(error: rustls::Error) -> Self {
+        Self::Tls(error)
+    }
+}
+
+impl fmt::Display for TlsError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => fmt.write_fmt(format_args!("I/O error: {error}")),
+            Self::Tls(error) => fmt.write_fmt(format_args!("TLS error: {error}")),
+        }
+    }
+}
+
+impl std::error::Error for TlsError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Tls(error) => Some(error),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+///TLS transport, as per [RFC 5425](https://datatracker.ietf.org/doc/html/rfc5425)
+///
+///Messages are always framed using octet counting.
+pub struct Tls {
+    remote_addr: net::SocketAddr,
+    server_name: ServerName<'static>,
+    config: Arc<ClientConfig>,
+    timeout: Option<time::Duration>,
+}
+
+impl Tls {
+    #[inline]
+    fn config_builder() -> Result<rustls::ConfigBuilder<ClientConfig, rustls::WantsVerifier>, rustls::Error> {
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider())).with_safe_default_protocol_versions()
+    }
+
+    ///Creates new transport which verifies server certificate using `roots`
+    ///
+    ///Server name is remote address' IP unless overridden by `with_server_name`
+    pub fn new(remote_addr: net::SocketAddr, roots: RootCertStore) -> Result<Self, rustls::Error> {
+        let config = Self::config_builder()?.with_root_certificates(roots).with_no_client_auth();
+        Ok(Self::from_config(remote_addr, Arc::new(config)))
+    }
+
+    ///Creates new transport which verifies server certificate using `roots` and authenticates itself using client certificate (i.e. mutual TLS)
+    ///
+    ///Server name is remote address' IP unless overridden by `with_server_name`
+    pub fn with_client_auth(remote_addr: net::SocketAddr, roots: RootCertStore, cert_chain: Vec<CertificateDer<'static>>, key: PrivateKeyDer<'static>) -> Result<Self, rustls::Error> {
+        let config = Self::config_builder()?.with_root_certificates(roots).with_client_auth_cert(cert_chain, key)?;
+        Ok(Self::from_config(remote_addr, Arc::new(config)))
+    }
+
+    ///Creates new transport using custom client configuration
+    ///
+    ///Server name is remote address' IP unless overridden by `with_server_name`
+    pub fn from_config(remote_addr: net::SocketAddr, config: Arc<ClientConfig>) -> Self {
+        Self {
+            remote_addr,
+            server_name: ServerName::IpAddress(remote_addr.ip().into()),
+            config,
+            timeout: None,
+        }
+    }
+
+    ///Overrides server name used to verify server certificate
+    pub fn with_server_name(mut self, server_name: ServerName<'static>) -> Self {
+        self.server_name = server_name;
+        self
+    }
+
+    ///Sets timeout on all socket operations, including handshake.
+    ///
+    ///Defaults to no setting (i.e. system default)
+    pub fn with_timeout(mut self, timeout: time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+impl MakeTransport for Tls {
+    type Error = TlsError;
+    type Transport = TlsSocket;
+
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        let mut socket = match self.timeout {
+            Some(timeout) => net::TcpStream::connect_timeout(&self.remote_addr, timeout)?,
+            None => net::TcpStream::connect(self.remote_addr)?,
+        };
+        socket.set_read_timeout(self.timeout)?;
+        socket.set_write_timeout(self.timeout)?;
+
+        let mut connection = ClientConnection::new(self.config.clone(), self.server_name.clone())?;
+        //Complete handshake right away, so that certificate errors are reported on creation
+        while connection.is_handshaking() {
+            connection.complete_io(&mut socket)?;
+        }
+
+        Ok(TlsSocket {
+            stream: StreamOwned::new(connection, socket),
+        })
+    }
+}
+
+///TLS socket wrapper which closes connection on Drop
+pub struct TlsSocket {
+    stream: StreamOwned<ClientConnection, net::TcpStream>,
+}
+
+impl Transport<TlsError> for TlsSocket {
+    #[inline(always)]
+    fn write(&mut self, _severity: Severity, msg: &str) -> Result<(), TlsError> {
+        Framing::OctetCounting.write(&mut self.stream, msg).map_err(TlsError::from)
+    }
+}
+
+impl Drop for TlsSocket {
+    fn drop(&mut self) {
+        self.stream.conn.send_close_notify();
+        let _ = io::Write::flush(&mut self.stream);
+        let _ = self.stream.sock.shutdown(net::Shutdown::Both);
+    }
+}
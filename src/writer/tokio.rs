@@ -0,0 +1,157 @@
+extern crate std;
+
+use core::future::Future;
+use core::time;
+use std::io;
+
+use tokio::io::AsyncWriteExt;
+
+use super::{AsyncMakeTransport, AsyncTransport};
+use super::std::{frame_slices, FramedTcp, Framing, Tcp, Udp, Unix, LOCAL_HOST};
+use crate::syslog::Severity;
+
+#[inline(always)]
+async fn with_timeout<T>(timeout: Option<time::Duration>, fut: impl Future<Output = Result<T, io::Error>>) -> Result<T, io::Error> {
+    match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, fut).await {
+            Ok(result) => result,
+            Err(_) => Err(io::Error::new(io::ErrorKind::TimedOut, "syslog operation timed out")),
+        },
+        None => fut.await,
+    }
+}
+
+impl AsyncMakeTransport for Udp {
+    type Error = io::Error;
+    type Transport = tokio::net::UdpSocket;
+
+    #[inline(always)]
+    async fn create(&self) -> Result<Self::Transport, Self::Error> {
+        let socket = tokio::net::UdpSocket::bind((LOCAL_HOST, self.local_port)).await?;
+        socket.connect(self.remote_addr).await?;
+        Ok(socket)
+    }
+}
+
+impl AsyncTransport<io::Error> for tokio::net::UdpSocket {
+    #[inline(always)]
+    async fn write(&mut self, _severity: Severity, msg: &str) -> Result<(), io::Error> {
+        self.send(msg.as_bytes()).await.map(|_| ())
+    }
+}
+
+impl AsyncMakeTransport for Tcp {
+    type Error = io::Error;
+    type Transport = AsyncTcpSocket;
+
+    #[inline(always)]
+    async fn create(&self) -> Result<Self::Transport, Self::Error> {
+        AsyncMakeTransport::create(&self.with_framing(Framing::NonTransparent)).await
+    }
+}
+
+impl AsyncMakeTransport for FramedTcp {
+    type Error = io::Error;
+    type Transport = AsyncTcpSocket;
+
+    #[inline(always)]
+    async fn create(&self) -> Result<Self::Transport, Self::Error> {
+        let socket = with_timeout(self.tcp.timeout, tokio::net::TcpStream::connect(self.tcp.remote_addr)).await?;
+        Ok(AsyncTcpSocket {
+            socket,
+            framing: self.framing,
+            timeout: self.tcp.timeout,
+        })
+    }
+}
+
+///Async TCP socket using configured framing
+///
+///Socket is closed on Drop
+pub struct AsyncTcpSocket {
+    socket: tokio::net::TcpStream,
+    framing: Framing,
+    timeout: Option<time::Duration>,
+}
+
+impl AsyncTcpSocket {
+    #[inline(always)]
+    ///Creates new instance using specified `framing`, without timeout
+    pub const fn new(socket: tokio::net::TcpStream, framing: Framing) -> Self {
+        Self {
+            socket,
+            framing,
+            timeout: None,
+        }
+    }
+
+    async fn write_framed(&mut self, msg: &str) -> Result<(), io::Error> {
+        let prefix = self.framing.encode_prefix(msg);
+        let frame = [prefix.as_str().as_bytes(), msg.as_bytes(), self.framing.suffix()];
+        let mut written = 0;
+        loop {
+            let slices = frame_slices(&frame, written);
+            if slices.iter().all(|slice| slice.is_empty()) {
+                break;
+            }
+            match self.socket.write_vectored(&slices).await? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                size => written += size,
+            }
+        }
+        self.socket.flush().await
+    }
+}
+
+impl AsyncTransport<io::Error> for AsyncTcpSocket {
+    #[inline(always)]
+    async fn write(&mut self, _severity: Severity, msg: &str) -> Result<(), io::Error> {
+        let timeout = self.timeout;
+        with_timeout(timeout, self.write_framed(msg)).await
+    }
+}
+
+impl<'a> AsyncMakeTransport for Unix<'a> {
+    type Error = io::Error;
+    type Transport = AsyncUnixSocket;
+
+    #[inline(always)]
+    async fn create(&self) -> Result<Self::Transport, Self::Error> {
+        #[cfg(unix)]
+        {
+            let socket = tokio::net::UnixDatagram::unbound()?;
+            socket.connect(self.path)?;
+            Ok(AsyncUnixSocket {
+                socket,
+                timeout: self.timeout,
+            })
+        }
+
+        #[cfg(not(unix))]
+        {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "Unix socket is only supported on unix systems"));
+        }
+    }
+}
+
+///Wrapper over async Unix socket
+pub struct AsyncUnixSocket {
+    #[cfg(unix)]
+    socket: tokio::net::UnixDatagram,
+    #[cfg(unix)]
+    timeout: Option<time::Duration>,
+}
+
+impl AsyncTransport<io::Error> for AsyncUnixSocket {
+    #[inline(always)]
+    async fn write(&mut self, _severity: Severity, _msg: &str) -> Result<(), io::Error> {
+        #[cfg(unix)]
+        {
+            return with_timeout(self.timeout, self.socket.send(_msg.as_bytes())).await.map(|_| ());
+        }
+        #[cfg(not(unix))]
+        {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "Unix socket is only supported on unix systems"));
+        }
+    }
+}
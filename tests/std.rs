@@ -2,10 +2,15 @@ use core::time;
 use std::io;
 use std::sync::mpsc;
 
-use syslog_client::syslog::header::{Tag, Hostname};
+use syslog_client::syslog::header::{self, Tag, Hostname};
+use syslog_client::syslog::parser::Rfc5424Record;
+use syslog_client::testing::FixedClock;
 use syslog_client::writer::transport;
 use syslog_client::{Facility, Severity, Syslog};
 
+//2024-12-31T23:59:59Z
+const TIMESTAMP: u64 = 1_735_689_599;
+
 #[test]
 fn should_generate_rfc3164_messages_in_memory() {
     const TAG: Tag = match Tag::new("inmemory") {
@@ -17,20 +22,17 @@ fn should_generate_rfc3164_messages_in_memory() {
         None => panic!("not valid hostname"),
     };
 
+    let pid = std::process::id();
     let (sender, receiver) = mpsc::channel();
-    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).rfc3164(transport::InMemory::<String>::new(sender)).with_buffer();
+    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc3164(transport::InMemory::<String>::new(sender)).with_buffer();
     logger.write_str(Severity::LOG_ERR, "my error").expect("Success");
 
-    let mut line = receiver.try_recv().expect("to have line");
-
+    let line = receiver.try_recv().expect("to have line");
     println!("line={line}");
-    let mut line_split = line.rsplitn(2, ':');
-    let log = line_split.next().unwrap();
-    let header = line_split.next().unwrap();
-    let header_size = header.len() + 2;
-    assert_eq!(log, " my error");
+    let header = format!("<11>Dec 31 23:59:59 in.memory inmemory[{pid}]: ");
+    assert_eq!(line, format!("{header}my error"));
 
-    let chunk1_size = 1024 - header_size;
+    let chunk1_size = 1024 - header.len();
     println!("chunk1_size={chunk1_size}");
 
     //check split behavior
@@ -39,15 +41,67 @@ fn should_generate_rfc3164_messages_in_memory() {
 
     logger.write_str(Severity::LOG_ERR, &message).expect("Success");
 
-    line = receiver.try_recv().expect("to have line 1");
+    let line = receiver.try_recv().expect("to have line 1");
     println!("line1={line}");
-    let mut line_split = line.rsplitn(2, ':');
-    let log = line_split.next().unwrap().trim_start();
-    assert_eq!(log, &message[..chunk1_size]);
+    assert_eq!(line, format!("{header}{}", &message[..chunk1_size]));
 
-    line = receiver.try_recv().expect("to have line 2");
+    let line = receiver.try_recv().expect("to have line 2");
     println!("line2={line}");
-    assert!(line.ends_with(": 0"));
+    assert_eq!(line, format!("{header}0"));
+    assert!(receiver.try_recv().is_err());
+}
+
+#[test]
+fn should_generate_rfc3164_messages_with_utc_offset() {
+    const TAG: Tag = match Tag::new("inmemory") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.memory") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let pid = std::process::id();
+    let (sender, receiver) = mpsc::channel();
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).with_utc_offset(9 * 60);
+    let mut logger = syslog.rfc3164(transport::InMemory::<String>::new(sender)).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "my error").expect("Success");
+
+    let line = receiver.try_recv().expect("to have line");
+    assert_eq!(line, format!("<11>Jan  1 08:59:59 in.memory inmemory[{pid}]: my error"));
+
+    //Local offset is determined by the system for every record
+    let (sender, receiver) = mpsc::channel();
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).with_local_offset();
+    let mut logger = syslog.rfc5424(transport::InMemory::<String>::new(sender)).with_buffer();
+    logger.write_str(Severity::LOG_ERR, None, "my error").expect("Success");
+
+    let line = receiver.try_recv().expect("to have line");
+    let record = Rfc5424Record::parse(&line).expect("to parse record");
+    let offset = header::local_offset().expect("to have local offset");
+    assert_eq!(record.timestamp, Some(header::Timestamp::from_unix(TIMESTAMP, None).to_offset(offset)));
+}
+
+#[test]
+fn should_generate_rfc5424_messages_in_memory() {
+    const TAG: Tag = match Tag::new("inmemory") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.memory") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let pid = std::process::id();
+    let (sender, receiver) = mpsc::channel();
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).with_utc_offset(-(3 * 60 + 30));
+    let mut logger = syslog.rfc5424(transport::InMemory::<String>::new(sender)).with_buffer();
+    logger.write_str(Severity::LOG_ERR, None, "my error").expect("Success");
+
+    let line = receiver.try_recv().expect("to have line");
+    assert_eq!(line, format!("<11>1 2024-12-31T20:29:59-03:30 in.memory inmemory {pid} - - my error"));
 }
 
 #[test]
@@ -93,6 +147,141 @@ fn should_generate_rfc3164_messages_tcp() {
     }
 }
 
+#[test]
+fn should_frame_tcp_messages() {
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    const TAG: Tag = match Tag::new("tcp") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.tcp") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let pid = std::process::id();
+    let header = format!("<11>Dec 31 23:59:59 in.tcp tcp[{pid}]:");
+    for framing in [transport::Framing::NonTransparent, transport::Framing::OctetCounting] {
+        let server = TcpListener::bind((transport::LOCAL_HOST, 0)).expect("to bind server");
+        let tcp = transport::Tcp {
+            remote_addr: server.local_addr().expect("to have address"),
+            timeout: Some(time::Duration::from_secs(5)),
+        }.with_framing(framing);
+
+        let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc3164(tcp).with_buffer();
+        logger.write_str(Severity::LOG_ERR, "my tcp error").expect("Success");
+        logger.write_str(Severity::LOG_ERR, "multi\nline").expect("Success");
+        //Connection is closed on drop
+        drop(logger);
+
+        let (mut client, _) = server.accept().expect("to accept client");
+        let mut received = String::new();
+        client.read_to_string(&mut received).expect("to read");
+
+        let line1 = format!("{header} my tcp error");
+        let line2 = format!("{header} multi\nline");
+        let expected = match framing {
+            transport::Framing::NonTransparent => format!("{line1}\n{line2}\n"),
+            transport::Framing::OctetCounting => format!("{} {line1}{} {line2}", line1.len(), line2.len()),
+        };
+        assert_eq!(received, expected);
+    }
+}
+
+//Stream accepting limited number of bytes per write
+struct Chunked {
+    limit: usize,
+    writes: usize,
+    output: Vec<u8>,
+}
+
+impl io::Write for Chunked {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_vectored(&[io::IoSlice::new(buf)])
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.writes += 1;
+        let start = self.output.len();
+        for buf in bufs {
+            let size = core::cmp::min(buf.len(), self.limit - (self.output.len() - start));
+            self.output.extend_from_slice(&buf[..size]);
+        }
+        Ok(self.output.len() - start)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn should_write_framed_message_at_once() {
+    for (framing, expected) in [(transport::Framing::NonTransparent, "framed msg\n"), (transport::Framing::OctetCounting, "10 framed msg")] {
+        for limit in [usize::MAX, 3] {
+            let mut out = Chunked {
+                limit,
+                writes: 0,
+                output: Vec::new(),
+            };
+            framing.write(&mut out, "framed msg").expect("to write");
+            assert_eq!(out.output, expected.as_bytes());
+            assert_eq!(out.writes, expected.len().div_ceil(limit));
+        }
+    }
+}
+
+#[test]
+fn should_share_logger_between_threads() {
+    use std::net::TcpListener;
+
+    const TAG: Tag = match Tag::new("shared") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.tcp") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+    const THREADS: usize = 4;
+    const RECORDS: usize = 10;
+
+    let server = TcpListener::bind((transport::LOCAL_HOST, 0)).expect("to bind server");
+    let tcp = transport::Tcp {
+        remote_addr: server.local_addr().expect("to have address"),
+        timeout: Some(time::Duration::from_secs(5)),
+    };
+
+    let logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc3164(tcp).shared();
+    std::thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let logger = &logger;
+            scope.spawn(move || {
+                for record in 0..RECORDS {
+                    logger.write_str(Severity::LOG_ERR, &format!("thread={thread} record={record}")).expect("Success");
+                }
+            });
+        }
+    });
+    //Connection is closed on drop
+    drop(logger);
+
+    //All records must be written using single connection
+    let received = read_single_connection(server);
+
+    let pid = std::process::id();
+    let header = format!("<11>Dec 31 23:59:59 in.tcp shared[{pid}]: ");
+    let mut lines: Vec<&str> = received.lines().collect();
+    assert_eq!(lines.len(), THREADS * RECORDS);
+    lines.sort_unstable();
+    let header = header.as_str();
+    let mut expected: Vec<String> = (0..THREADS).flat_map(|thread| (0..RECORDS).map(move |record| format!("{header}thread={thread} record={record}"))).collect();
+    expected.sort_unstable();
+    assert_eq!(lines, expected);
+}
+
 #[cfg(unix)]
 #[test]
 fn should_generate_rfc3164_messages_unix() {
@@ -127,8 +316,9 @@ fn should_generate_rfc3164_messages_log04() {
         None => panic!("not valid hostname"),
     };
 
+    let pid = std::process::id();
     let (sender, receiver) = mpsc::channel();
-    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG);
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
     let writer = transport::InMemory::<String>::new(sender);
     let logger = Rfc3164Logger::new(syslog, writer);
 
@@ -136,24 +326,50 @@ fn should_generate_rfc3164_messages_log04() {
     log04::set_max_level(log04::LevelFilter::Info);
     log04::info!("Some info log");
 
-    let mut line = receiver.try_recv().expect("to have line");
-
+    let line = receiver.try_recv().expect("to have line");
     println!("line1={line}");
-    let mut line_split = line.rsplitn(2, ':');
-    let log = line_split.next().unwrap();
-    let _header = line_split.next().unwrap();
-    assert_eq!(log, " Some info log");
+    assert_eq!(line, format!("<13>Dec 31 23:59:59 in.log04 log04[{pid}]: Some info log"));
 
     log04::debug!("Should not show debug log");
     assert!(receiver.try_recv().is_err(), "Debug logs are filtered out");
 
     log04::warn!(error = "ERROR"; "Some warning log");
-    line = receiver.try_recv().expect("to have line");
+    let line = receiver.try_recv().expect("to have line");
     println!("line2={line}");
-    line_split = line.rsplitn(2, ':');
-    let log = line_split.next().unwrap();
-    let _header = line_split.next().unwrap();
-    assert_eq!(log, " Some warning log [KV error=ERROR]");
+    assert_eq!(line, format!("<12>Dec 31 23:59:59 in.log04 log04[{pid}]: Some warning log [KV error=ERROR]"));
+}
+
+#[cfg(feature = "log04")]
+#[test]
+fn should_sanitize_log04_messages_and_key_values() {
+    use log04::Log;
+    use syslog_client::sanitize::SanitizePolicy;
+    use syslog_client::syslog::structured_data::SdId;
+
+    const TAG: Tag = match Tag::new("log04") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.log04") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let pid = std::process::id();
+    let (sender, receiver) = mpsc::channel();
+    let syslog = || Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).with_sanitize(SanitizePolicy::Escape);
+    let key_values = [("user", "admin\n<13>fake")];
+
+    let logger = syslog_client::log04::Rfc3164Logger::new(syslog(), transport::InMemory::<String>::new(sender.clone()));
+    logger.log(&log04::Record::builder().level(log04::Level::Warn).args(format_args!("login\nfailed")).key_values(&key_values).build());
+    let line = receiver.try_recv().expect("to have line");
+    assert_eq!(line, format!("<12>Dec 31 23:59:59 in.log04 log04[{pid}]: login#012failed [KV user=admin#012<13>fake]"));
+
+    let sd_id = SdId::new("kv@32473").expect("valid sd id");
+    let logger = syslog_client::log04::Rfc5424Logger::new(syslog(), transport::InMemory::<String>::new(sender), sd_id);
+    logger.log(&log04::Record::builder().level(log04::Level::Warn).args(format_args!("login\nfailed")).key_values(&key_values).build());
+    let line = receiver.try_recv().expect("to have line");
+    assert!(line.ends_with(" [kv@32473 user=\"admin#012<13>fake\"] login#012failed"), "line={}", line);
 }
 
 #[cfg(feature = "tracing")]
@@ -179,38 +395,751 @@ fn should_generate_rfc3164_messages_tracing() {
         None => panic!("not valid hostname"),
     };
 
+    let pid = std::process::id();
     let (sender, receiver) = mpsc::channel();
-    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG);
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
     let writer = transport::InMemory::<String>::new(sender);
     let logger = Rfc3164Layer::new(syslog, writer);
+    let header = format!("Dec 31 23:59:59 in.tracing tracing[{pid}]:");
 
     let _guard = tracing_subscriber::registry().with(logger).set_default();
     tracing::info!("Some info log");
 
-    let mut line = receiver.try_recv().expect("to have line");
-
+    let line = receiver.try_recv().expect("to have line");
     println!("line1={line}");
-    let mut line_split = line.rsplitn(2, ':');
-    let log = line_split.next().unwrap();
-    let _header = line_split.next().unwrap();
-    assert_eq!(log, " Some info log");
+    assert_eq!(line, format!("<13>{header} Some info log"));
 
     tracing::warn!(error = "ERROR", "Some warning log");
-    line = receiver.try_recv().expect("to have line");
+    let line = receiver.try_recv().expect("to have line");
     println!("line2={line}");
-    line_split = line.rsplitn(2, ':');
-    let log = line_split.next().unwrap();
-    let _header = line_split.next().unwrap();
-    assert_eq!(log, " Some warning log error=ERROR");
+    assert_eq!(line, format!("<12>{header} Some warning log error=ERROR"));
 
     my_span("test", "value");
-    line = receiver.try_recv().expect("to have line");
+    let line = receiver.try_recv().expect("to have line");
     println!("line3={line}");
-    line_split = line.rsplitn(2, ':');
-    let log = line_split.next().unwrap();
-    let _header = line_split.next().unwrap();
     #[cfg(not(feature = "tracing-full"))]
-    assert_eq!(log, " EVENT(key=test) value=\"value\"");
+    assert_eq!(line, format!("<13>{header} EVENT(key=test) value=\"value\""));
     #[cfg(feature = "tracing-full")]
-    assert_eq!(log, " EVENT(key=test) value=\"value\" [my_span key=test value=\"value\"]");
+    assert_eq!(line, format!("<13>{header} EVENT(key=test) value=\"value\" [my_span key=test value=\"value\"]"));
+}
+
+#[cfg(feature = "log04")]
+#[test]
+fn should_generate_rfc5424_messages_log04() {
+    use log04::Log;
+    use syslog_client::log04::Rfc5424Logger;
+    use syslog_client::syslog::structured_data::{SdId, TruncationPolicy};
+
+    const TAG: Tag = match Tag::new("log04") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.log04") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+    const SD_ID: SdId = match SdId::new("kv@32473") {
+        Some(sd_id) => sd_id,
+        None => panic!("not valid sd id"),
+    };
+
+    let pid = std::process::id();
+    let (sender, receiver) = mpsc::channel();
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
+    let writer = transport::InMemory::<String>::new(sender);
+    let logger = Rfc5424Logger::new(syslog, writer, SD_ID).with_max_value_size(9).with_truncation(TruncationPolicy::Truncate);
+    let header = format!("1 2024-12-31T23:59:59Z in.log04 log04 {pid} -");
+
+    logger.log(&log04::Record::builder().level(log04::Level::Info).args(format_args!("Some info log")).build());
+    let line = receiver.try_recv().expect("to have line");
+    println!("line1={line}");
+    assert_eq!(line, format!("<13>{header} - Some info log"));
+
+    let key_values = [("error", "\"ERROR\""), ("long", "1234567890"), ("invalid key", "value")];
+    logger.log(&log04::Record::builder().level(log04::Level::Warn).args(format_args!("Some {} log", "warning")).key_values(&key_values).build());
+    let line = receiver.try_recv().expect("to have line");
+    println!("line2={line}");
+    assert_eq!(line, format!("<12>{header} [kv@32473 error=\"\\\"ERROR\\\"\" long=\"123456...\"] Some warning log"));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn should_generate_rfc5424_messages_tracing() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+    use syslog_client::syslog::structured_data::SdId;
+    use syslog_client::tracing::{MsgIdSource, Rfc5424Layer};
+
+    #[tracing::instrument]
+    fn my_span(key: &str) {
+        tracing::info!(value = "[value]", "EVENT(key={key})");
+    }
+
+    const TAG: Tag = match Tag::new("tracing") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.tracing") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+    const SD_ID: SdId = match SdId::new("event@32473") {
+        Some(sd_id) => sd_id,
+        None => panic!("not valid sd id"),
+    };
+
+    let pid = std::process::id();
+    let (sender, receiver) = mpsc::channel();
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
+    let writer = transport::InMemory::<String>::new(sender.clone());
+    let logger = Rfc5424Layer::new(syslog, writer, SD_ID);
+    let header = format!("1 2024-12-31T23:59:59Z in.tracing tracing {pid}");
+
+    let guard = tracing_subscriber::registry().with(logger).set_default();
+    tracing::info!("Some info log");
+    let line = receiver.try_recv().expect("to have line");
+    println!("line1={line}");
+    assert_eq!(line, format!("<13>{header} std - Some info log"));
+
+    tracing::warn!(error = "ERROR", code = 5, "Some warning log");
+    let line = receiver.try_recv().expect("to have line");
+    println!("line2={line}");
+    assert_eq!(line, format!("<12>{header} std [event@32473 error=\"ERROR\" code=\"5\"] Some warning log"));
+
+    my_span("test");
+    let line = receiver.try_recv().expect("to have line");
+    println!("line3={line}");
+    #[cfg(not(feature = "tracing-full"))]
+    assert_eq!(line, format!("<13>{header} std [event@32473 value=\"[value\\]\"] EVENT(key=test)"));
+    #[cfg(feature = "tracing-full")]
+    assert_eq!(line, format!("<13>{header} std [event@32473 value=\"[value\\]\"][my_span key=\"test\"] EVENT(key=test)"));
+    drop(guard);
+
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
+    let writer = transport::InMemory::<String>::new(sender);
+    let logger = Rfc5424Layer::new(syslog, writer, SD_ID).with_msg_id(MsgIdSource::Field("kind"));
+
+    let _guard = tracing_subscriber::registry().with(logger).set_default();
+    tracing::info!(kind = "audit", "Some audit log");
+    let line = receiver.try_recv().expect("to have line");
+    println!("line4={line}");
+    assert_eq!(line, format!("<13>{header} audit - Some audit log"));
+
+    tracing::info!(kind = "not valid", user = "me", "Some audit log");
+    let line = receiver.try_recv().expect("to have line");
+    println!("line5={line}");
+    assert_eq!(line, format!("<13>{header} - [event@32473 user=\"me\"] Some audit log"));
+}
+
+#[cfg(feature = "tracing-full")]
+#[test]
+fn should_not_repeat_span_sd_id_tracing() {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+    use syslog_client::syslog::structured_data::SdId;
+    use syslog_client::tracing::Rfc5424Layer;
+
+    #[tracing::instrument]
+    fn recursive(depth: u8) {
+        if depth == 0 {
+            tracing::info!(value = 1, "EVENT");
+        } else {
+            recursive(depth - 1);
+        }
+    }
+
+    const TAG: Tag = match Tag::new("tracing") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.tracing") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+    const SD_ID: SdId = match SdId::new("event") {
+        Some(sd_id) => sd_id,
+        None => panic!("not valid sd id"),
+    };
+
+    let pid = std::process::id();
+    let (sender, receiver) = mpsc::channel();
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
+    let writer = transport::InMemory::<String>::new(sender);
+    let logger = Rfc5424Layer::new(syslog, writer, SD_ID);
+    let header = format!("1 2024-12-31T23:59:59Z in.tracing tracing {pid}");
+
+    let _guard = tracing_subscriber::registry().with(logger).set_default();
+    recursive(2);
+    let line = receiver.try_recv().expect("to have line");
+    println!("line1={line}");
+    assert_eq!(line, format!("<13>{header} std [event value=\"1\"][recursive depth=\"0\"] EVENT"));
+
+    //Span named same as event's element
+    tracing::info_span!("event", outer = 1).in_scope(|| {
+        tracing::info!(value = 2, "EVENT");
+        tracing::info!("NO FIELDS");
+    });
+    let line = receiver.try_recv().expect("to have line");
+    println!("line2={line}");
+    assert_eq!(line, format!("<13>{header} std [event value=\"2\"] EVENT"));
+    let line = receiver.try_recv().expect("to have line");
+    println!("line3={line}");
+    assert_eq!(line, format!("<13>{header} std [event outer=\"1\"] NO FIELDS"));
+}
+
+///Accepts single connection, verifying there is no other, and reads everything sent over it
+fn read_single_connection(server: std::net::TcpListener) -> String {
+    use std::io::Read;
+
+    let (mut client, _) = server.accept().expect("to accept client");
+    server.set_nonblocking(true).expect("to set non-blocking");
+    assert!(server.accept().is_err(), "Only single connection is expected");
+
+    let mut received = String::new();
+    client.read_to_string(&mut received).expect("to read");
+    received
+}
+
+#[cfg(feature = "log04")]
+#[test]
+fn should_keep_connection_log04() {
+    use log04::Log;
+    use std::net::TcpListener;
+    use syslog_client::log04::Rfc3164Logger;
+
+    const TAG: Tag = match Tag::new("log04") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.log04") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let server = TcpListener::bind((transport::LOCAL_HOST, 0)).expect("to bind server");
+    let tcp = transport::Tcp {
+        remote_addr: server.local_addr().expect("to have address"),
+        timeout: Some(time::Duration::from_secs(5)),
+    };
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
+    let logger = Rfc3164Logger::new(syslog, tcp);
+
+    logger.log(&log04::Record::builder().level(log04::Level::Info).args(format_args!("first")).build());
+    logger.log(&log04::Record::builder().level(log04::Level::Warn).args(format_args!("second")).build());
+    //Connection is closed on drop
+    drop(logger);
+
+    let pid = std::process::id();
+    let received = read_single_connection(server);
+    assert_eq!(received, format!("<13>Dec 31 23:59:59 in.log04 log04[{pid}]: first\n<12>Dec 31 23:59:59 in.log04 log04[{pid}]: second\n"));
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn should_keep_connection_tracing() {
+    use std::net::TcpListener;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+    use syslog_client::tracing::Rfc3164Layer;
+
+    const TAG: Tag = match Tag::new("tracing") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.tracing") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let server = TcpListener::bind((transport::LOCAL_HOST, 0)).expect("to bind server");
+    let tcp = transport::Tcp {
+        remote_addr: server.local_addr().expect("to have address"),
+        timeout: Some(time::Duration::from_secs(5)),
+    }.with_framing(transport::Framing::OctetCounting);
+    let syslog = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
+    let logger = Rfc3164Layer::new(syslog, tcp);
+
+    let guard = tracing_subscriber::registry().with(logger).set_default();
+    tracing::info!("first");
+    tracing::warn!("second");
+    //Connection is closed once layer is dropped
+    drop(guard);
+
+    let pid = std::process::id();
+    let line1 = format!("<13>Dec 31 23:59:59 in.tracing tracing[{pid}]: first");
+    let line2 = format!("<12>Dec 31 23:59:59 in.tracing tracing[{pid}]: second");
+    let received = read_single_connection(server);
+    assert_eq!(received, format!("{} {line1}{} {line2}", line1.len(), line2.len()));
+}
+
+///Transport which notifies when write starts and then waits for gate to open
+#[derive(Clone)]
+struct Gated {
+    started: mpsc::Sender<()>,
+    gate: std::sync::Arc<std::sync::Mutex<()>>,
+    output: mpsc::Sender<String>,
+}
+
+impl syslog_client::writer::MakeTransport for Gated {
+    type Error = mpsc::SendError<String>;
+    type Transport = Self;
+
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl syslog_client::writer::Transport<mpsc::SendError<String>> for Gated {
+    fn write(&mut self, _severity: Severity, msg: &str) -> Result<(), mpsc::SendError<String>> {
+        let _ = self.started.send(());
+        let _gate = self.gate.lock().expect("to lock gate");
+        self.output.send(msg.to_owned())
+    }
+}
+
+#[test]
+fn should_write_in_background() {
+    use transport::{Background, BackgroundConfig, Overflow};
+
+    const TAG: Tag = match Tag::new("background") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.memory") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let pid = std::process::id();
+    let header = format!("<11>Dec 31 23:59:59 in.memory background[{pid}]: ");
+    let syslog = || Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
+
+    let (sender, receiver) = mpsc::channel();
+    let background = Background::spawn(transport::InMemory::<String>::new(sender), BackgroundConfig::default()).expect("to spawn worker");
+    let mut logger = syslog().rfc3164(&background).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "first").expect("Success");
+    logger.write_str(Severity::LOG_ERR, "second").expect("Success");
+    background.flush();
+    assert_eq!(receiver.try_recv().expect("to have line 1"), format!("{header}first"));
+    assert_eq!(receiver.try_recv().expect("to have line 2"), format!("{header}second"));
+    assert!(receiver.try_recv().is_err());
+
+    drop(logger);
+    drop(background);
+
+    for (overflow, expected) in [(Overflow::DropNewest, ["0", "1", "2"]), (Overflow::DropOldest, ["0", "2", "3"])] {
+        let (started, started_receiver) = mpsc::channel();
+        let (output, receiver) = mpsc::channel();
+        let gate = std::sync::Arc::new(std::sync::Mutex::new(()));
+        let transport = Gated {
+            started,
+            gate: gate.clone(),
+            output,
+        };
+        let config = BackgroundConfig {
+            capacity: 2,
+            overflow,
+            retry_count: 0,
+        };
+
+        let background = Background::spawn(transport, config).expect("to spawn worker");
+        let mut logger = syslog().rfc3164(&background).with_buffer();
+        let closed_gate = gate.lock().expect("to lock gate");
+        logger.write_str(Severity::LOG_ERR, "0").expect("Success");
+        //Wait for worker to be blocked on first record
+        started_receiver.recv().expect("to start write");
+        for idx in 1..4 {
+            logger.write_str(Severity::LOG_ERR, &idx.to_string()).expect("Success");
+        }
+        assert_eq!(background.dropped(), 1);
+        drop(closed_gate);
+
+        //Remaining records are written on drop
+        drop(logger);
+        drop(background);
+        let lines: Vec<String> = receiver.try_iter().collect();
+        let expected: Vec<String> = expected.iter().map(|text| format!("{header}{text}")).collect();
+        assert_eq!(lines, expected, "Unexpected result of {:?}", overflow);
+    }
+}
+
+#[test]
+fn should_failover_to_secondary() {
+    use std::net::TcpListener;
+    use transport::{Failover, Target};
+
+    const TAG: Tag = match Tag::new("failover") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.tcp") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    //Primary is not available initially
+    let server = TcpListener::bind((transport::LOCAL_HOST, 0)).expect("to bind server");
+    let remote_addr = server.local_addr().expect("to have address");
+    drop(server);
+
+    let tcp = transport::Tcp {
+        remote_addr,
+        timeout: Some(time::Duration::from_secs(5)),
+    };
+    let (sender, receiver) = mpsc::channel();
+    //Logger owns its transport, while active target is still observable
+    let failover = std::sync::Arc::new(Failover::new(tcp, transport::InMemory::<String>::new(sender)).with_probe_interval(2));
+    assert_eq!(failover.active(), Target::Primary);
+
+    let pid = std::process::id();
+    let header = format!("<11>Dec 31 23:59:59 in.tcp failover[{pid}]: ");
+    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc3164(failover.clone()).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "first").expect("Success");
+    assert_eq!(failover.active(), Target::Secondary);
+    assert_eq!(receiver.try_recv().expect("to have line 1"), format!("{header}first"));
+
+    //Primary is probed only on second record
+    let server = TcpListener::bind(remote_addr).expect("to bind server again");
+    logger.write_str(Severity::LOG_ERR, "second").expect("Success");
+    assert_eq!(failover.active(), Target::Secondary);
+    assert_eq!(receiver.try_recv().expect("to have line 2"), format!("{header}second"));
+
+    logger.write_str(Severity::LOG_ERR, "third").expect("Success");
+    assert_eq!(failover.active(), Target::Primary);
+    assert!(receiver.try_recv().is_err());
+    logger.write_str(Severity::LOG_ERR, "fourth").expect("Success");
+    drop(logger);
+
+    let received = read_single_connection(server);
+    assert_eq!(received, format!("{header}third\n{header}fourth\n"));
+}
+
+#[test]
+fn should_write_to_every_tee_leg() {
+    use std::net::TcpListener;
+    use syslog_client::writer::TransportError;
+    use transport::Tee;
+
+    const TAG: Tag = match Tag::new("tee") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.memory") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let pid = std::process::id();
+    let header = format!("<11>Dec 31 23:59:59 in.memory tee[{pid}]: ");
+    let syslog = || Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
+
+    let (first_sender, first_receiver) = mpsc::channel();
+    let (second_sender, second_receiver) = mpsc::channel();
+    let tee = Tee::new(transport::InMemory::<String>::new(first_sender), transport::InMemory::<String>::new(second_sender));
+    let mut logger = syslog().rfc3164(&tee).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "my error").expect("Success");
+    assert_eq!(first_receiver.try_recv().expect("to have line"), format!("{header}my error"));
+    assert_eq!(second_receiver.try_recv().expect("to have line"), format!("{header}my error"));
+
+    //Failed leg is retried without duplicating record on other leg
+    let server = TcpListener::bind((transport::LOCAL_HOST, 0)).expect("to bind server");
+    let remote_addr = server.local_addr().expect("to have address");
+    drop(server);
+    let tcp = transport::Tcp {
+        remote_addr,
+        timeout: Some(time::Duration::from_secs(5)),
+    };
+    let (sender, receiver) = mpsc::channel();
+    let tee = Tee::new(tcp, transport::InMemory::<String>::new(sender));
+    let mut logger = syslog().with_retry_count(2).rfc3164(&tee).with_buffer();
+    let error = logger.write_str(Severity::LOG_ERR, "my error").expect_err("Should fail");
+    assert!(error.is_first_failed());
+    assert!(!error.is_second_failed());
+    //Re-creating tee would duplicate record on second leg
+    assert!(!error.is_terminal());
+    assert_eq!(error.first.expect("first error").kind(), io::ErrorKind::ConnectionRefused);
+    assert_eq!(receiver.try_recv().expect("to have line"), format!("{header}my error"));
+    assert!(receiver.try_recv().is_err());
+
+    //Nothing is written, so terminal error of leg is reported
+    let tee = std::sync::Arc::new(Tee::new(tcp, tcp));
+    let mut logger = syslog().with_retry_count(2).rfc3164(tee).with_buffer();
+    let error = logger.write_str(Severity::LOG_ERR, "my error").expect_err("Should fail");
+    assert!(error.is_first_failed());
+    assert!(error.is_second_failed());
+    assert!(error.is_terminal());
+}
+
+#[test]
+fn should_route_by_severity() {
+    use syslog_client::writer::TransportError;
+    use transport::Router;
+
+    const TAG: Tag = match Tag::new("router") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.memory") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let (error_sender, error_receiver) = mpsc::channel();
+    let (warning_sender, warning_receiver) = mpsc::channel();
+    let (notice_sender, notice_receiver) = mpsc::channel();
+    let (sender, receiver) = mpsc::channel();
+    let notices = Router::new([(Severity::LOG_NOTICE..=Severity::LOG_NOTICE, transport::InMemory::<String>::new(notice_sender))], transport::InMemory::<String>::new(sender));
+    assert_eq!(notices.route(Severity::LOG_NOTICE), Some(0));
+    let routes = [
+        (Severity::LOG_EMERG..=Severity::LOG_ERR, transport::InMemory::<String>::new(error_sender)),
+        //Overlap is resolved in favour of the first route
+        (Severity::LOG_WARNING..=Severity::LOG_CRIT, transport::InMemory::<String>::new(warning_sender)),
+    ];
+    let router = Router::new(routes, std::sync::Arc::new(notices));
+    assert_eq!(router.route(Severity::LOG_CRIT), Some(0));
+    assert_eq!(router.route(Severity::LOG_WARNING), Some(1));
+    assert!(!router.is_routed(Severity::LOG_NOTICE));
+
+    let pid = std::process::id();
+    let header = format!("Dec 31 23:59:59 in.memory router[{pid}]:");
+    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc3164(&router).with_buffer();
+    logger.write_str(Severity::LOG_CRIT, "critical").expect("Success");
+    logger.write_str(Severity::LOG_ERR, "error").expect("Success");
+    logger.write_str(Severity::LOG_WARNING, "warning").expect("Success");
+    logger.write_str(Severity::LOG_NOTICE, "notice").expect("Success");
+    logger.write_str(Severity::LOG_DEBUG, "debug").expect("Success");
+
+    assert_eq!(error_receiver.try_iter().collect::<Vec<_>>(), [format!("<10>{header} critical"), format!("<11>{header} error")]);
+    assert_eq!(warning_receiver.try_iter().collect::<Vec<_>>(), [format!("<12>{header} warning")]);
+    assert_eq!(notice_receiver.try_iter().collect::<Vec<_>>(), [format!("<13>{header} notice")]);
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [format!("<15>{header} debug")]);
+
+    //Error of route is reported as it is
+    let server = std::net::TcpListener::bind((transport::LOCAL_HOST, 0)).expect("to bind server");
+    let tcp = transport::Tcp {
+        remote_addr: server.local_addr().expect("to have address"),
+        timeout: Some(time::Duration::from_secs(5)),
+    };
+    drop(server);
+    let (sender, receiver) = mpsc::channel();
+    let router = Router::new([(Severity::LOG_EMERG..=Severity::LOG_ERR, tcp)], transport::InMemory::<String>::new(sender));
+    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc3164(&router).with_buffer();
+    let error = logger.write_str(Severity::LOG_ERR, "error").expect_err("Should fail");
+    assert!(matches!(error, transport::RouterError::Route(_)));
+    assert!(error.is_terminal());
+    assert!(receiver.try_recv().is_err());
+}
+
+#[derive(Clone)]
+struct Switch {
+    is_up: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    output: mpsc::Sender<String>,
+}
+
+impl syslog_client::writer::MakeTransport for Switch {
+    type Error = io::Error;
+    type Transport = Self;
+
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        if self.is_up.load(std::sync::atomic::Ordering::Acquire) {
+            Ok(self.clone())
+        } else {
+            Err(io::ErrorKind::ConnectionRefused.into())
+        }
+    }
+}
+
+impl syslog_client::writer::Transport<io::Error> for Switch {
+    fn write(&mut self, _severity: Severity, msg: &str) -> Result<(), io::Error> {
+        if self.is_up.load(std::sync::atomic::Ordering::Acquire) {
+            self.output.send(msg.to_owned()).map_err(|_| io::ErrorKind::BrokenPipe.into())
+        } else {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+}
+
+#[test]
+fn should_spool_and_replay_after_restart() {
+    use std::sync::atomic::Ordering;
+    use transport::{Spool, SpoolConfig};
+
+    const TAG: Tag = match Tag::new("spool") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: Hostname = match Hostname::new("in.memory") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+
+    let pid = std::process::id();
+    let header = format!("<11>Dec 31 23:59:59 in.memory spool[{pid}]: ");
+    let path = std::env::temp_dir().join(format!("syslog-client-spool-{pid}"));
+    let _ = std::fs::remove_file(&path);
+
+    let (sender, receiver) = mpsc::channel();
+    let transport = Switch {
+        is_up: Default::default(),
+        output: sender,
+    };
+    let syslog = || Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP));
+
+    let spool = Spool::open(&path, transport.clone(), SpoolConfig::default()).expect("to open spool");
+    let mut logger = syslog().rfc3164(&spool).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "first").expect("to spool");
+    logger.write_str(Severity::LOG_ERR, "multi\nline").expect("to spool");
+    assert_eq!(spool.len(), 2);
+    drop(logger);
+    drop(spool);
+
+    //Restart with some garbage written on crash
+    {
+        use std::io::Write;
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).expect("to open spool file");
+        file.write_all(b"1 3 100\nincomplete").expect("to write garbage");
+    }
+    let spool = Spool::open(&path, transport.clone(), SpoolConfig::default()).expect("to re-open spool");
+    assert_eq!(spool.len(), 2);
+    assert!(receiver.try_recv().is_err());
+
+    transport.is_up.store(true, Ordering::Release);
+    let mut logger = syslog().rfc3164(&spool).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "third").expect("Success");
+    assert!(spool.is_empty());
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [format!("{header}first"), format!("{header}multi\nline"), format!("{header}third")]);
+    drop(logger);
+    drop(spool);
+    assert_eq!(std::fs::read(&path).expect("to read spool file").len(), 0);
+
+    //Expired records are discarded
+    std::fs::write(&path, "0 3 5\nstale\n").expect("to write spool file");
+    let spool = Spool::open(&path, transport.clone(), SpoolConfig::default()).expect("to open spool");
+    assert!(spool.is_empty());
+    assert_eq!(spool.dropped(), 1);
+    drop(spool);
+
+    //Oldest records are discarded to fit size limit
+    transport.is_up.store(false, Ordering::Release);
+    let config = SpoolConfig {
+        max_size: 2 * (header.len() as u64 + 40),
+        ..SpoolConfig::default()
+    };
+    let spool = Spool::open(&path, transport.clone(), config).expect("to open spool");
+    let mut logger = syslog().rfc3164(&spool).with_buffer();
+    for idx in 0..4 {
+        logger.write_str(Severity::LOG_ERR, &format!("record {idx}")).expect("to spool");
+    }
+    assert_eq!(spool.len(), 2);
+    assert_eq!(spool.dropped(), 2);
+    drop(logger);
+    drop(spool);
+
+    //Discarded records are not loaded again
+    let spool = std::sync::Arc::new(Spool::open(&path, transport.clone(), config).expect("to re-open spool"));
+    assert_eq!(spool.len(), 2);
+    assert_eq!(spool.dropped(), 0);
+
+    transport.is_up.store(true, Ordering::Release);
+    let mut logger = syslog().rfc3164(spool.clone()).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "record 4").expect("Success");
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [format!("{header}record 2"), format!("{header}record 3"), format!("{header}record 4")]);
+
+    drop(logger);
+    drop(spool);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[derive(Clone)]
+//Transport that fails while down and panics while broken
+struct Flaky {
+    //0 - down, 1 - broken, 2 - up
+    state: std::sync::Arc<std::sync::atomic::AtomicU8>,
+    output: mpsc::Sender<String>,
+}
+
+impl syslog_client::writer::MakeTransport for Flaky {
+    type Error = io::Error;
+    type Transport = Self;
+
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl syslog_client::writer::Transport<io::Error> for Flaky {
+    fn write(&mut self, _severity: Severity, msg: &str) -> Result<(), io::Error> {
+        match self.state.load(std::sync::atomic::Ordering::Acquire) {
+            0 => Err(io::ErrorKind::BrokenPipe.into()),
+            1 => panic!("broken transport"),
+            _ => self.output.send(msg.to_owned()).map_err(|_| io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+}
+
+#[test]
+fn should_resume_replay_after_transport_panic() {
+    use std::sync::atomic::Ordering;
+    use transport::{Spool, SpoolConfig};
+
+    let pid = std::process::id();
+    let path = std::env::temp_dir().join(format!("syslog-client-spool-panic-{pid}"));
+    let _ = std::fs::remove_file(&path);
+
+    let (sender, receiver) = mpsc::channel();
+    let transport = Flaky {
+        state: Default::default(),
+        output: sender,
+    };
+    let syslog = || Syslog::new(Facility::LOG_USER, Hostname::new("in.memory").unwrap(), Tag::new("spool").unwrap()).with_clock(&FixedClock(TIMESTAMP));
+    let header = format!("<11>Dec 31 23:59:59 in.memory spool[{pid}]: ");
+
+    let spool = Spool::open(&path, transport.clone(), SpoolConfig::default()).expect("to open spool");
+    let mut logger = syslog().rfc3164(&spool).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "first").expect("to spool");
+    assert_eq!(spool.len(), 1);
+
+    transport.state.store(1, Ordering::Release);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| logger.write_str(Severity::LOG_ERR, "second")));
+    assert!(result.is_err());
+    drop(logger);
+    assert_eq!(spool.len(), 1);
+
+    transport.state.store(2, Ordering::Release);
+    let mut logger = syslog().rfc3164(&spool).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "third").expect("Success");
+    assert!(spool.is_empty());
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [format!("{header}first"), format!("{header}third")]);
+
+    drop(logger);
+    drop(spool);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn should_detect_hostname_and_tag_from_env() {
+    let hostname = Hostname::from_system().expect("to detect hostname");
+    assert!(Hostname::new(hostname.as_str()).is_some(), "hostname={}", hostname.as_str());
+    if let Ok(kernel) = std::fs::read_to_string("/proc/sys/kernel/hostname") {
+        assert_eq!(hostname.as_str(), kernel.trim());
+    }
+    let fqdn = Hostname::from_system_fqdn().expect("to detect fqdn");
+    assert_eq!(fqdn.as_str().split('.').next(), hostname.as_str().split('.').next());
+
+    let tag = Tag::from_current_exe().expect("to detect process name");
+    assert!(Tag::new(tag.as_str()).is_some(), "tag={}", tag.as_str());
+    //Test binary is named after test target
+    assert!(tag.as_str().starts_with("std"), "tag={}", tag.as_str());
+
+    let pid = std::process::id();
+    let (sender, receiver) = mpsc::channel();
+    let mut logger = Syslog::from_env(Facility::LOG_USER).with_clock(&FixedClock(TIMESTAMP)).rfc3164(transport::InMemory::<String>::new(sender.clone())).with_buffer();
+    logger.write_str(Severity::LOG_INFO, "detected").expect("Success");
+    let line = receiver.try_recv().expect("to have line");
+    assert_eq!(line, format!("<14>Dec 31 23:59:59 {} {}[{pid}]: detected", hostname.as_str(), tag.as_str()));
+
+    let mut logger = Syslog::from_env(Facility::LOG_USER).with_hostname(fqdn).with_clock(&FixedClock(TIMESTAMP)).rfc3164(transport::InMemory::<String>::new(sender)).with_buffer();
+    logger.write_str(Severity::LOG_INFO, "fqdn").expect("Success");
+    let line = receiver.try_recv().expect("to have line");
+    assert_eq!(line, format!("<14>Dec 31 23:59:59 {} {}[{pid}]: fqdn", fqdn.as_str(), tag.as_str()));
 }
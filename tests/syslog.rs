@@ -1,26 +1,88 @@
+use core::cell::RefCell;
+use std::rc::Rc;
+
 use syslog_client::syslog::header;
+use syslog_client::syslog::parser::{ErrorKind, ParseError, Rfc3164Record, Rfc5424Record};
+use syslog_client::sanitize::SanitizePolicy;
+use syslog_client::split::{Split, SplitStrategy};
+use syslog_client::writer::{Backoff, CircuitBreaker, MakeTransport, RetryPolicy, Timer, Transport, TransportError};
+use syslog_client::{Facility, Severity, Syslog, Rfc3164BufferedLogger};
 
 #[test]
 fn should_verify_header_tag_ctor() {
     assert!(header::Tag::new("").is_none());
 
     let mut text = String::new();
-    for idx in 0..32 {
+    for idx in 0..48 {
         text.push(char::from(b'a' + idx % 9));
         let tag = header::Tag::new(&text).expect("to create tag");
         assert_eq!(text, tag.as_str());
     }
     text.push('z');
     assert!(header::Tag::new(&text).is_none());
+
+    for name in ["my-service", "api_gw", "nginx/worker"] {
+        assert_eq!(header::Tag::new(name).expect("to create tag").as_str(), name);
+    }
+    assert!(header::Tag::new("my service").is_none());
+    assert!(header::Tag::new("процесс").is_none());
+
+    assert_eq!(header::Tag::new_lossy("my service").as_str(), "my_service");
+    assert_eq!(header::Tag::new_lossy("процесс").as_str(), "_______");
+    assert_eq!(header::Tag::new_lossy("").as_str(), "-");
+    assert_eq!(header::Tag::new_lossy(&"a".repeat(60)).as_str(), "a".repeat(48));
+}
+
+#[test]
+fn should_verify_header_hostname_ctor() {
+    assert!(header::Hostname::new("").is_none());
+    for name in ["localhost", "my_host", "host-1.example.com", "192.168.0.1", "fe80::1"] {
+        assert_eq!(header::Hostname::new(name).expect("to create hostname").as_str(), name);
+    }
+    assert!(header::Hostname::new("my host").is_none());
+    assert!(header::Hostname::new("host..com").is_none());
+    assert!(header::Hostname::new(".host").is_none());
+    assert!(header::Hostname::new("host..").is_none());
+    assert!(header::Hostname::new(".").is_none());
+    assert_eq!(header::Hostname::new("example.com.").expect("to create hostname").as_str(), "example.com.");
+
+    let label = "a".repeat(63);
+    let fqdn = [label.as_str(); 4].join(".");
+    assert_eq!(fqdn.len(), 255);
+    assert!(header::Hostname::new(&fqdn).is_some());
+    assert!(header::Hostname::new(&format!("{fqdn}a")).is_none());
+    assert!(header::Hostname::new(&format!("{label}a")).is_none());
+
+    assert_eq!(header::Hostname::new_lossy("my host..example.com.").as_str(), "my-host.example.com");
+    assert_eq!(header::Hostname::new_lossy("хост.local").as_str(), "----.local");
+    assert_eq!(header::Hostname::new_lossy("...").as_str(), "-");
+    assert_eq!(header::Hostname::new_lossy(&format!("{label}bbb.com")).as_str(), format!("{label}.com"));
+    let lossy = header::Hostname::new_lossy(&[label.as_str(); 5].join("."));
+    assert_eq!(lossy.as_str(), fqdn);
+}
+
+#[test]
+fn should_verify_header_msg_id_ctor() {
+    assert!(header::MsgId::new("").is_none());
+    assert!(header::MsgId::new("my id").is_none());
+    assert!(header::MsgId::new("ид").is_none());
+    assert_eq!(header::MsgId::new("my_crate::module").expect("valid msg id").as_str(), "my_crate::module");
+    assert!(header::MsgId::new(&"a".repeat(32)).is_some());
+    assert!(header::MsgId::new(&"a".repeat(33)).is_none());
 }
 
 #[test]
 fn should_generate_rfc3164_header() {
-    assert_eq!(header::Rfc3164::SIZE, 131);
+    assert_eq!(header::Rfc3164::SIZE, 338);
 
     let mut hostname = String::new();
     for idx in 0..64 {
-        hostname.push((b'a' + idx % 9) as char);
+        //Label is limited to 63 characters
+        if idx == 31 {
+            hostname.push('.');
+        } else {
+            hostname.push((b'a' + idx % 9) as char);
+        }
     }
     let hostname = header::Hostname::new(&hostname).expect("to create 64 long hostname");
 
@@ -39,22 +101,29 @@ fn should_generate_rfc3164_header() {
             sec: 59,
             min: 59,
             hour: 24,
+            micros: None,
+            offset: 0,
         },
         hostname: &hostname,
         tag: &tag,
         pid: u32::MAX,
     };
     let buffer = header.create_buffer();
-    assert_eq!(buffer, "<255>Jan  1 24:59:59 abcdefghiabcdefghiabcdefghiabcdefghiabcdefghiabcdefghiabcdefghia abcdefghiabcdefghiabcdefghiabcde[4294967295]:");
+    assert_eq!(buffer, "<255>Jan  1 24:59:59 abcdefghiabcdefghiabcdefghiabcd.fghiabcdefghiabcdefghiabcdefghia abcdefghiabcdefghiabcdefghiabcde[4294967295]:");
 }
 
 #[test]
 fn should_generate_rfc5424_header() {
-    assert_eq!(header::Rfc5424::SIZE, 167);
+    assert_eq!(header::Rfc5424::SIZE, 388);
 
     let mut hostname = String::new();
     for idx in 0..64 {
-        hostname.push((b'a' + idx % 9) as char);
+        //Label is limited to 63 characters
+        if idx == 31 {
+            hostname.push('.');
+        } else {
+            hostname.push((b'a' + idx % 9) as char);
+        }
     }
     let hostname = header::Hostname::new(&hostname).expect("to create 64 long hostname");
 
@@ -68,7 +137,7 @@ fn should_generate_rfc5424_header() {
     for idx in 0..32 {
         msg_id.push((b'b' + idx % 9) as char);
     }
-    let msg_id = header::Tag::new(&msg_id).expect("to create 32 long msg_id");
+    let msg_id = header::MsgId::new(&msg_id).expect("to create 32 long msg_id");
 
     let header = header::Rfc5424 {
         pri: u8::MAX,
@@ -79,12 +148,940 @@ fn should_generate_rfc5424_header() {
             sec: 59,
             min: 59,
             hour: 24,
+            micros: None,
+            offset: 0,
         },
         hostname: &hostname,
         tag: &tag,
-        msg_id: &msg_id,
+        msg_id: Some(&msg_id),
         pid: u32::MAX,
     };
     let buffer = header.create_buffer();
-    assert_eq!(buffer, "<255>2024-01-01T24:59:59Z abcdefghiabcdefghiabcdefghiabcdefghiabcdefghiabcdefghiabcdefghia abcdefghiabcdefghiabcdefghiabcde 4294967295 bcdefghijbcdefghijbcdefghijbcdef");
+    assert_eq!(buffer, "<255>1 2024-01-01T24:59:59Z abcdefghiabcdefghiabcdefghiabcd.fghiabcdefghiabcdefghiabcdefghia abcdefghiabcdefghiabcdefghiabcde 4294967295 bcdefghijbcdefghijbcdefghijbcdef");
+}
+
+#[test]
+fn should_convert_timestamp() {
+    let timestamp = header::Timestamp::from_unix(0, None);
+    assert_eq!((timestamp.year, timestamp.month, timestamp.day, timestamp.hour, timestamp.min, timestamp.sec), (1970, 0, 1, 0, 0, 0));
+
+    //Leap day
+    let timestamp = header::Timestamp::from_unix(951_825_599, Some(5));
+    assert_eq!((timestamp.year, timestamp.month, timestamp.day, timestamp.hour, timestamp.min, timestamp.sec), (2000, 1, 29, 11, 59, 59));
+    assert_eq!(timestamp.micros, Some(5));
+    assert_eq!(timestamp.offset, 0);
+
+    let timestamp = header::Timestamp::from_unix(1_735_689_599, None);
+    assert_eq!((timestamp.year, timestamp.month, timestamp.day, timestamp.hour, timestamp.min, timestamp.sec), (2024, 11, 31, 23, 59, 59));
+
+    //Crossing year boundary forward
+    let local = timestamp.to_offset(9 * 60);
+    assert_eq!((local.year, local.month, local.day, local.hour, local.min, local.sec), (2025, 0, 1, 8, 59, 59));
+    assert_eq!(local.offset, 540);
+
+    //Crossing day boundary backward
+    let local = local.to_offset(-(3 * 60 + 30));
+    assert_eq!((local.year, local.month, local.day, local.hour, local.min, local.sec), (2024, 11, 31, 20, 29, 59));
+    assert_eq!(local.offset, -210);
+
+    let utc = local.to_offset(0);
+    assert_eq!((utc.year, utc.month, utc.day, utc.hour, utc.min, utc.sec), (2024, 11, 31, 23, 59, 59));
+}
+
+#[test]
+fn should_generate_rfc5424_header_timestamp() {
+    let hostname = header::Hostname::new("host").expect("valid hostname");
+    let tag = header::Tag::new("tag").expect("valid tag");
+
+    let mut header = header::Rfc5424 {
+        pri: 14,
+        timestamp: header::Timestamp::from_unix(1_735_689_599, Some(1234)),
+        hostname: &hostname,
+        tag: &tag,
+        msg_id: None,
+        pid: 1,
+    };
+    assert_eq!(header.create_buffer(), "<14>1 2024-12-31T23:59:59.001234Z host tag 1 -");
+
+    header.timestamp = header::Timestamp::from_unix(1_735_689_599, None).to_offset(-(3 * 60 + 30));
+    assert_eq!(header.create_buffer(), "<14>1 2024-12-31T20:29:59-03:30 host tag 1 -");
+
+    header.timestamp = header::Timestamp::from_unix(1_735_689_599, Some(999_999)).to_offset(9 * 60);
+    assert_eq!(header.create_buffer(), "<14>1 2025-01-01T08:59:59.999999+09:00 host tag 1 -");
+
+    //Offset beyond TIME-NUMOFFSET range is clamped
+    header.timestamp = header::Timestamp::from_unix(1_735_689_599, None).to_offset(3000);
+    assert_eq!(header.timestamp.offset, header::MAX_UTC_OFFSET);
+    assert_eq!(header.create_buffer(), "<14>1 2025-01-01T23:58:59+23:59 host tag 1 -");
+
+    header.timestamp = header::Timestamp::from_unix(1_735_689_599, None).to_offset(-3000);
+    assert_eq!(header.create_buffer(), "<14>1 2024-12-31T00:00:59-23:59 host tag 1 -");
+
+    let header = header::Rfc3164 {
+        pri: 14,
+        timestamp: header::Timestamp::from_unix(1_735_689_599, Some(999_999)).to_offset(9 * 60),
+        hostname: &hostname,
+        tag: &tag,
+        pid: 1,
+    };
+    assert_eq!(header.create_buffer(), "<14>Jan  1 08:59:59 host tag[1]:");
+}
+
+#[derive(Clone, Default)]
+struct Collector(Rc<RefCell<Vec<String>>>);
+
+#[derive(Debug)]
+struct Closed;
+
+impl TransportError for Closed {
+    fn is_terminal(&self) -> bool {
+        true
+    }
+}
+
+impl Transport<Closed> for Collector {
+    fn write(&mut self, _severity: Severity, msg: &str) -> Result<(), Closed> {
+        self.0.borrow_mut().push(msg.to_owned());
+        Ok(())
+    }
+}
+
+impl MakeTransport for Collector {
+    type Error = Closed;
+    type Transport = Self;
+
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+impl Collector {
+    fn pop(&self) -> Option<String> {
+        let mut lines = self.0.borrow_mut();
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.remove(0))
+        }
+    }
+}
+
+#[test]
+fn should_generate_rfc5424_messages() {
+    use core::fmt::Write;
+
+    const TAG: header::Tag = match header::Tag::new("rfc5424") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    const HOSTNAME: header::Hostname = match header::Hostname::new("in.memory") {
+        Some(hostname) => hostname,
+        None => panic!("not valid hostname"),
+    };
+    let msg_id = header::MsgId::new("msgid").expect("valid msg id");
+    let pid = std::process::id();
+
+    let collector = Collector::default();
+    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).rfc5424(&collector).with_buffer();
+    logger.write_str(Severity::LOG_ERR, None, "my error").expect("Success");
+
+    let line = collector.pop().expect("to have line");
+    println!("line={line}");
+    let record = Rfc5424Record::parse(&line).expect("to parse record");
+    assert_eq!(record.facility, Facility::LOG_USER);
+    assert_eq!(record.severity, Severity::LOG_ERR);
+    let timestamp = record.timestamp.expect("to have timestamp");
+    assert_eq!(timestamp.offset, 0);
+    assert_eq!(record.hostname.expect("to have hostname").as_str(), "in.memory");
+    assert_eq!(record.tag.expect("to have tag").as_str(), "rfc5424");
+    assert_eq!(record.proc_id, Some(pid.to_string().as_str()));
+    assert!(record.msg_id.is_none());
+    assert!(record.structured_data.is_none());
+    assert_eq!(record.msg, "my error");
+    assert!(collector.pop().is_none());
+
+    logger.write_str(Severity::LOG_INFO, Some(&msg_id), "my info").expect("Success");
+    let line = collector.pop().expect("to have line");
+    println!("line={line}");
+    assert!(line.starts_with("<14>1 "));
+    assert!(line.ends_with(&format!(" in.memory rfc5424 {pid} msgid - my info")));
+
+    //check split behavior
+    let header_size = line.len() - "my info".len();
+    let chunk1_size = 2048 - header_size;
+    let mut message = "1".repeat(chunk1_size);
+    message.push('0');
+
+    logger.write_str(Severity::LOG_INFO, Some(&msg_id), &message).expect("Success");
+    let line = collector.pop().expect("to have line 1");
+    assert_eq!(line.len(), 2048);
+    assert!(line.ends_with(&format!(" msgid - {}", &message[..chunk1_size])));
+    let line = collector.pop().expect("to have line 2");
+    assert!(line.ends_with(" msgid - 0"));
+    assert!(collector.pop().is_none());
+
+    let mut record = logger.write_record(Severity::LOG_WARNING, None, None);
+    write!(record, "formatted {}", 1).expect("Success");
+    record.flush().expect("Success");
+    drop(record);
+    let line = collector.pop().expect("to have line");
+    assert!(line.starts_with("<12>1 "));
+    assert!(line.ends_with(" - - formatted 1"));
+}
+
+#[test]
+fn should_verify_structured_data_sd_id() {
+    use syslog_client::syslog::structured_data::SdId;
+
+    assert!(SdId::new("").is_none());
+    assert_eq!(SdId::new("origin").expect("valid id").as_str(), "origin");
+    assert_eq!(SdId::new("exampleSDID@32473").expect("valid id").as_str(), "exampleSDID@32473");
+    assert_eq!(SdId::new("ex@32473.1.2").expect("valid id").as_str(), "ex@32473.1.2");
+    assert!(SdId::new("@32473").is_none());
+    assert!(SdId::new("ex@").is_none());
+    assert!(SdId::new("ex@name").is_none());
+    assert!(SdId::new("ex@32473.").is_none());
+    assert!(SdId::new("ex@32473..1").is_none());
+    assert!(SdId::new("ex@1@2").is_none());
+    assert!(SdId::new("my id").is_none());
+    assert!(SdId::new("my=id").is_none());
+    assert!(SdId::new("my]id").is_none());
+    assert!(SdId::new("my\"id").is_none());
+    assert!(SdId::new("идентификатор").is_none());
+    assert!(SdId::new(&"a".repeat(32)).is_some());
+    assert!(SdId::new(&"a".repeat(33)).is_none());
+}
+
+#[test]
+fn should_build_structured_data() {
+    use syslog_client::syslog::structured_data::{self, SdId, StructuredData};
+
+    let mut data = StructuredData::new();
+    assert!(data.is_empty());
+    assert_eq!(data.to_string(), "-");
+
+    let id = SdId::new("exampleSDID@32473").expect("valid id");
+    {
+        let mut element = data.element(&id).expect("to have space");
+        assert!(element.param("iut", "3"));
+        assert!(element.param_fmt("eventSource", format_args!("{}", "Application")));
+        assert!(!element.param("invalid name", "value"));
+        assert!(!element.param("", "value"));
+        assert!(element.param("escaped", "a\"b\\c]d"));
+    }
+    {
+        let _ = data.element(&SdId::new("origin").expect("valid id")).expect("to have space");
+    }
+    assert_eq!(data.as_str(), "[exampleSDID@32473 iut=\"3\" eventSource=\"Application\" escaped=\"a\\\"b\\\\c\\]d\"][origin]");
+    assert!(data.contains(&id));
+    assert!(data.contains(&SdId::new("origin").expect("valid id")));
+    assert!(!data.contains(&SdId::new("exampleSDID").expect("valid id")));
+    assert!(!data.contains(&SdId::new("d").expect("valid id")));
+
+    //Param that doesn't fit is discarded, leaving element valid
+    data.clear();
+    let big_value = "1".repeat(structured_data::SIZE);
+    {
+        let mut element = data.element(&id).expect("to have space");
+        assert!(!element.param("big", &big_value));
+        assert!(element.param("small", "1"));
+    }
+    assert_eq!(data.as_str(), "[exampleSDID@32473 small=\"1\"]");
+
+    //Fill buffer completely, making sure closing bracket always fits
+    data.clear();
+    {
+        let mut element = data.element(&id).expect("to have space");
+        let value_size = structured_data::SIZE - "[exampleSDID@32473 big=\"\"]".len();
+        assert!(!element.param("big", &big_value[..value_size + 1]));
+        assert!(element.param("big", &big_value[..value_size]));
+    }
+    assert_eq!(data.as_str().len(), structured_data::SIZE);
+    assert!(data.as_str().ends_with("\"]"));
+    assert!(data.element(&id).is_none());
+}
+
+#[test]
+fn should_generate_rfc5424_messages_with_structured_data() {
+    use syslog_client::syslog::structured_data::{SdId, StructuredData};
+
+    let hostname = header::Hostname::new("in.memory").expect("valid hostname");
+    let tag = header::Tag::new("rfc5424").expect("valid tag");
+    let pid = std::process::id();
+
+    let mut data = StructuredData::new();
+    {
+        let mut element = data.element(&SdId::new("meta@32473").expect("valid id")).expect("to have space");
+        assert!(element.param("key", "[value]"));
+    }
+
+    let collector = Collector::default();
+    let mut logger = Syslog::new(Facility::LOG_USER, hostname, tag).rfc5424(&collector).with_buffer();
+    let mut record = logger.write_record(Severity::LOG_ERR, None, Some(&data));
+    record.write_str("my error").expect("Success");
+    record.flush().expect("Success");
+    drop(record);
+
+    let line = collector.pop().expect("to have line");
+    println!("line={line}");
+    assert!(line.starts_with("<11>1 "));
+    assert!(line.ends_with(&format!(" in.memory rfc5424 {pid} - [meta@32473 key=\"[value\\]\"] my error")));
+
+    //Structured data is omitted, unless record has room for message after it
+    let mut logger = Syslog::new(Facility::LOG_USER, hostname, tag).with_max_size(80).rfc5424(&collector).with_buffer();
+    let mut record = logger.write_record(Severity::LOG_ERR, None, Some(&data));
+    record.write_str("my error").expect("Success");
+    record.flush().expect("Success");
+    drop(record);
+
+    let line = collector.pop().expect("to have line");
+    assert!(line.ends_with(&format!(" in.memory rfc5424 {pid} - - my error")));
+}
+
+#[test]
+fn should_truncate_structured_data_values() {
+    use syslog_client::syslog::structured_data::{self, SdId, StructuredData, TruncationPolicy};
+
+    let id = SdId::new("kv@32473").expect("valid id");
+    let mut data = StructuredData::new();
+    {
+        let mut element = data.element(&id).expect("to have space");
+        assert!(element.param_fmt_limited("fit", format_args!("12345"), 5, TruncationPolicy::Omit));
+        assert!(!element.param_fmt_limited("omit", format_args!("123456"), 5, TruncationPolicy::Omit));
+        assert!(element.param_fmt_limited("cut", format_args!("123456"), 5, TruncationPolicy::Truncate));
+        //Escape sequence is never split
+        assert!(element.param_fmt_limited("esc", format_args!("1]]]]"), 6, TruncationPolicy::Truncate));
+        //Multi-byte characters are never split
+        assert!(element.param_fmt_limited("utf8", format_args!("ааа"), 5, TruncationPolicy::Truncate));
+        assert!(!element.param_fmt_limited("tiny", format_args!("123"), 2, TruncationPolicy::Truncate));
+    }
+    assert_eq!(data.as_str(), "[kv@32473 fit=\"12345\" cut=\"12...\" esc=\"1\\]...\" utf8=\"а...\"]");
+
+    //Value is truncated to fit remaining space
+    data.clear();
+    let big_value = "1".repeat(structured_data::SIZE);
+    {
+        let mut element = data.element(&id).expect("to have space");
+        assert!(element.param_fmt_limited("big", format_args!("{big_value}"), usize::MAX, TruncationPolicy::Truncate));
+    }
+    assert_eq!(data.as_str().len(), structured_data::SIZE);
+    assert!(data.as_str().ends_with("11...\"]"));
+}
+
+#[derive(Debug)]
+struct Unavailable {
+    is_terminal: bool,
+}
+
+impl TransportError for Unavailable {
+    fn is_terminal(&self) -> bool {
+        self.is_terminal
+    }
+}
+
+#[derive(Clone, Default)]
+struct Unreachable {
+    creates: Rc<core::cell::Cell<usize>>,
+    writes: Rc<core::cell::Cell<usize>>,
+    //When set, create succeeds while write fails with terminal error
+    is_write_failing: bool,
+    is_up: Rc<core::cell::Cell<bool>>,
+}
+
+impl Transport<Unavailable> for Unreachable {
+    fn write(&mut self, _severity: Severity, _msg: &str) -> Result<(), Unavailable> {
+        self.writes.set(self.writes.get() + 1);
+        if self.is_up.get() {
+            Ok(())
+        } else {
+            Err(Unavailable {
+                is_terminal: true,
+            })
+        }
+    }
+}
+
+impl MakeTransport for Unreachable {
+    type Error = Unavailable;
+    type Transport = Self;
+
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        self.creates.set(self.creates.get() + 1);
+        if self.is_up.get() || self.is_write_failing {
+            Ok(self.clone())
+        } else {
+            Err(Unavailable {
+                is_terminal: false,
+            })
+        }
+    }
+}
+
+struct ManualTimer(std::sync::Mutex<(core::time::Duration, Vec<core::time::Duration>)>);
+
+impl ManualTimer {
+    const fn new() -> Self {
+        Self(std::sync::Mutex::new((core::time::Duration::ZERO, Vec::new())))
+    }
+
+    fn advance(&self, duration: core::time::Duration) {
+        self.0.lock().unwrap().0 += duration;
+    }
+
+    fn take_sleeps(&self) -> Vec<core::time::Duration> {
+        core::mem::take(&mut self.0.lock().unwrap().1)
+    }
+}
+
+impl Timer for ManualTimer {
+    fn elapsed(&self) -> core::time::Duration {
+        self.0.lock().unwrap().0
+    }
+
+    fn sleep(&self, duration: core::time::Duration) {
+        let mut state = self.0.lock().unwrap();
+        state.0 += duration;
+        state.1.push(duration);
+    }
+}
+
+#[test]
+fn should_give_up_on_terminal_write_error() {
+    const TAG: header::Tag = match header::Tag::new("retry") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    let transport = Unreachable {
+        is_write_failing: true,
+        ..Unreachable::default()
+    };
+    let mut logger = Syslog::new(Facility::LOG_USER, header::Hostname::new("retry").unwrap(), TAG).with_retry_count(2).rfc3164(transport.clone()).with_buffer();
+    let error = logger.write_str(Severity::LOG_ERR, "lost").expect_err("to fail");
+    assert!(error.is_terminal());
+    assert_eq!(transport.creates.get(), 3);
+    assert_eq!(transport.writes.get(), 3);
+}
+
+#[test]
+fn should_back_off_and_open_circuit_breaker() {
+    use core::time::Duration;
+
+    const TAG: header::Tag = match header::Tag::new("retry") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    static TIMER: ManualTimer = ManualTimer::new();
+    static BREAKER: CircuitBreaker = CircuitBreaker::new(2, Duration::from_secs(10));
+    let backoff = Backoff {
+        initial: Duration::from_millis(100),
+        max: Duration::from_millis(250),
+        jitter: false,
+    };
+    let policy = RetryPolicy::new().with_backoff(backoff).with_circuit_breaker(&BREAKER).with_timer(&TIMER);
+
+    let transport = Unreachable::default();
+    let mut logger = Syslog::new(Facility::LOG_USER, header::Hostname::new("retry").unwrap(), TAG).with_retry_count(3)
+                                                                                                  .with_retry_policy(policy)
+                                                                                                  .rfc3164(transport.clone())
+                                                                                                  .with_buffer();
+    logger.write_str(Severity::LOG_ERR, "first").expect_err("to fail");
+    assert_eq!(transport.creates.get(), 4);
+    assert_eq!(TIMER.take_sleeps(), [Duration::from_millis(100), Duration::from_millis(200), Duration::from_millis(250)]);
+    assert!(!BREAKER.is_open());
+
+    logger.write_str(Severity::LOG_ERR, "second").expect_err("to fail");
+    assert_eq!(transport.creates.get(), 8);
+    assert!(BREAKER.is_open());
+    TIMER.take_sleeps();
+
+    //Records are dropped without attempts while open
+    logger.write_str(Severity::LOG_ERR, "dropped").expect("to drop");
+    assert_eq!(transport.creates.get(), 8);
+    assert_eq!(BREAKER.dropped(), 1);
+    assert!(TIMER.take_sleeps().is_empty());
+
+    //Failure after cool down opens breaker again
+    TIMER.advance(Duration::from_secs(10));
+    logger.write_str(Severity::LOG_ERR, "probe").expect_err("to fail");
+    assert_eq!(transport.creates.get(), 12);
+    assert!(BREAKER.is_open());
+    logger.write_str(Severity::LOG_ERR, "dropped").expect("to drop");
+    assert_eq!(BREAKER.dropped(), 2);
+
+    //Success after cool down closes breaker
+    TIMER.advance(Duration::from_secs(10));
+    transport.is_up.set(true);
+    logger.write_str(Severity::LOG_ERR, "written").expect("to write");
+    assert!(!BREAKER.is_open());
+    assert_eq!(transport.writes.get(), 1);
+}
+
+#[derive(Clone)]
+//Transport which blocks write while up, until it is released
+struct Gate {
+    is_up: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    entered: std::sync::mpsc::SyncSender<()>,
+    release: std::sync::Arc<std::sync::Mutex<std::sync::mpsc::Receiver<()>>>,
+}
+
+impl Transport<Unavailable> for Gate {
+    fn write(&mut self, _severity: Severity, _msg: &str) -> Result<(), Unavailable> {
+        if !self.is_up.load(std::sync::atomic::Ordering::Acquire) {
+            return Err(Unavailable {
+                is_terminal: true,
+            });
+        }
+        let _ = self.entered.send(());
+        let _ = self.release.lock().unwrap().recv();
+        Ok(())
+    }
+}
+
+impl MakeTransport for Gate {
+    type Error = Unavailable;
+    type Transport = Self;
+
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+#[test]
+fn should_let_single_probe_through_open_circuit_breaker() {
+    static TIMER: ManualTimer = ManualTimer::new();
+    static BREAKER: CircuitBreaker = CircuitBreaker::new(1, core::time::Duration::from_secs(10));
+    let policy = RetryPolicy::new().with_circuit_breaker(&BREAKER).with_timer(&TIMER);
+    let syslog = move || Syslog::new(Facility::LOG_USER, header::Hostname::new("probe").unwrap(), header::Tag::new("probe").unwrap()).with_retry_count(0).with_retry_policy(policy);
+
+    let (entered, entered_recv) = std::sync::mpsc::sync_channel(1);
+    let (release, release_recv) = std::sync::mpsc::channel();
+    let transport = Gate {
+        is_up: Default::default(),
+        entered,
+        release: std::sync::Arc::new(std::sync::Mutex::new(release_recv)),
+    };
+
+    let mut logger = syslog().rfc3164(transport.clone()).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "failed").expect_err("to fail");
+    assert!(BREAKER.is_open());
+
+    TIMER.advance(core::time::Duration::from_secs(10));
+    transport.is_up.store(true, std::sync::atomic::Ordering::Release);
+    let probe = {
+        let transport = transport.clone();
+        std::thread::spawn(move || syslog().rfc3164(transport).with_buffer().write_str(Severity::LOG_ERR, "probe").is_ok())
+    };
+    entered_recv.recv().expect("probe to start");
+
+    //Records are dropped while probe is in progress
+    logger.write_str(Severity::LOG_ERR, "dropped").expect("to drop");
+    assert_eq!(BREAKER.dropped(), 1);
+    assert_eq!(policy.dropped(), 1);
+    assert!(BREAKER.is_open());
+
+    release.send(()).expect("to release probe");
+    assert!(probe.join().expect("to finish probe"));
+    assert!(!BREAKER.is_open());
+}
+
+#[test]
+fn should_apply_jitter_within_upper_half_of_delay() {
+    use core::time::Duration;
+
+    let backoff = Backoff {
+        initial: Duration::from_millis(100),
+        max: Duration::from_secs(1),
+        jitter: true,
+    };
+    for seed in 0..64 {
+        for attempt in 1..6 {
+            let full = Duration::from_millis(100 << (attempt - 1)).min(backoff.max);
+            let delay = backoff.delay(attempt, seed);
+            assert!(delay >= full / 2 && delay <= full, "attempt={} delay={:?}", attempt, delay);
+        }
+    }
+    assert_ne!(backoff.delay(1, 1), backoff.delay(1, 2));
+}
+
+#[test]
+fn should_parse_rfc3164_record() {
+    const TAG: header::Tag = match header::Tag::new("rfc3164") {
+        Some(tag) => tag,
+        None => panic!("not valid tag"),
+    };
+    let collector = Collector::default();
+    fn clock() -> header::Timestamp {
+        header::Timestamp::from_unix(1_735_603_199, None)
+    }
+    let syslog = Syslog::new(Facility::LOG_LOCAL3, header::Hostname::new("in.memory").unwrap(), TAG);
+    let mut logger = syslog.with_clock(&clock).rfc3164(collector.clone()).with_buffer();
+    logger.write_str(Severity::LOG_WARNING, "my: warning").expect("Success");
+
+    let line = collector.pop().expect("to have line");
+    let record = Rfc3164Record::parse(&line).expect("to parse record");
+    assert_eq!(record.facility, Facility::LOG_LOCAL3);
+    assert_eq!(record.severity, Severity::LOG_WARNING);
+    assert_eq!((record.timestamp.month, record.timestamp.day), (11, 30));
+    assert_eq!((record.timestamp.hour, record.timestamp.min, record.timestamp.sec), (23, 59, 59));
+    assert_eq!(record.timestamp.year, 0);
+    assert_eq!(record.hostname.as_str(), "in.memory");
+    assert_eq!(record.tag.as_str(), "rfc3164");
+    assert_eq!(record.pid, Some(std::process::id()));
+    assert_eq!(record.msg, "my: warning");
+
+    let record = Rfc3164Record::parse("<100>Feb  3 04:05:06 host ntpd: sync").expect("to parse record");
+    assert_eq!(record.facility, Facility::LOG_NTP);
+    assert_eq!(record.severity, Severity::LOG_WARNING);
+    assert_eq!(Facility::from_priority(Facility::LOG_CLOCK as u8), Some(Facility::LOG_CLOCK));
+
+    let record = Rfc3164Record::parse("<0>Feb  3 04:05:06 host kernel: panic").expect("to parse record");
+    assert_eq!(record.facility, Facility::LOG_KERN);
+    assert_eq!(record.severity, Severity::LOG_EMERG);
+    assert_eq!((record.timestamp.month, record.timestamp.day), (1, 3));
+    assert_eq!(record.tag.as_str(), "kernel");
+    assert_eq!(record.pid, None);
+    assert_eq!(record.msg, "panic");
+
+    //TAG cannot contain `[` or `:`
+    let syslog = Syslog::new(Facility::LOG_LOCAL3, header::Hostname::new("in.memory").unwrap(), header::Tag::new("app[1]:x").unwrap());
+    let mut logger = syslog.with_clock(&clock).rfc3164(collector.clone()).with_buffer();
+    logger.write_str(Severity::LOG_WARNING, "my warning").expect("Success");
+    let line = collector.pop().expect("to have line");
+    let record = Rfc3164Record::parse(&line).expect("to parse record");
+    assert_eq!(record.tag.as_str(), "app_1]_x");
+    assert_eq!(record.pid, Some(std::process::id()));
+    assert_eq!(record.msg, "my warning");
+}
+
+#[test]
+fn should_parse_rfc5424_record() {
+    let line = "<165>1 2003-10-11T22:14:15.003-07:00 mymachine.example.com evntslog - ID47 [exampleSDID@32473 iut=\"3\" eventSource=\"Appl\\\"ic\\]at\\\\ion\"][examplePriority@32473 class=\"high\"] \u{feff}An application event";
+    let record = Rfc5424Record::parse(line).expect("to parse record");
+    assert_eq!(record.facility, Facility::LOG_LOCAL4);
+    assert_eq!(record.severity, Severity::LOG_NOTICE);
+    assert_eq!(record.timestamp, Some(header::Timestamp {
+        year: 2003,
+        month: 9,
+        day: 11,
+        hour: 22,
+        min: 14,
+        sec: 15,
+        micros: Some(3000),
+        offset: -420,
+    }));
+    assert_eq!(record.hostname.expect("to have hostname").as_str(), "mymachine.example.com");
+    assert_eq!(record.tag.expect("to have tag").as_str(), "evntslog");
+    assert_eq!(record.proc_id, None);
+    assert_eq!(record.msg_id.expect("to have msg id").as_str(), "ID47");
+    assert_eq!(record.msg, "An application event");
+
+    let structured_data = record.structured_data.expect("to have structured data");
+    let elements = structured_data.elements().collect::<Vec<_>>();
+    assert_eq!(elements.len(), 2);
+    assert_eq!(elements[0].id, "exampleSDID@32473");
+    let params = elements[0].params().map(|param| (param.name, param.value.to_string())).collect::<Vec<_>>();
+    assert_eq!(params, [("iut", "3".to_owned()), ("eventSource", "Appl\"ic]at\\ion".to_owned())]);
+    assert_eq!(elements[1].id, "examplePriority@32473");
+    assert_eq!(elements[1].params().map(|param| param.value.as_raw()).collect::<Vec<_>>(), ["high"]);
+
+    let record = Rfc5424Record::parse("<13>1 - - - 42 - -").expect("to parse nil record");
+    assert!(record.timestamp.is_none());
+    assert!(record.hostname.is_none());
+    assert!(record.tag.is_none());
+    assert_eq!(record.proc_id, Some("42"));
+    assert!(record.structured_data.is_none());
+    assert_eq!(record.msg, "");
+
+    let record = Rfc5424Record::parse("<13>1 - - - worker-1 - - msg").expect("to parse record");
+    assert_eq!(record.proc_id, Some("worker-1"));
+    assert_eq!(record, record.clone());
+}
+
+#[test]
+fn should_report_parse_errors() {
+    let error = |kind, offset| Some(ParseError {
+        kind,
+        offset,
+    });
+
+    assert_eq!(Rfc3164Record::parse("").err(), error(ErrorKind::UnexpectedEnd, 0));
+    assert_eq!(Rfc3164Record::parse("13>Jan  1 00:00:00 host tag: msg").err(), error(ErrorKind::Pri, 0));
+    assert_eq!(Rfc3164Record::parse("<999>Jan  1 00:00:00 host tag: msg").err(), error(ErrorKind::Pri, 1));
+    assert_eq!(Rfc3164Record::parse("<192>Jan  1 00:00:00 host tag: msg").err(), error(ErrorKind::Pri, 1));
+    assert_eq!(Rfc3164Record::parse("<013>Jan  1 00:00:00 host tag: msg").err(), error(ErrorKind::Pri, 1));
+    assert_eq!(Rfc3164Record::parse("<13>Foo  1 00:00:00 host tag: msg").err(), error(ErrorKind::Timestamp, 4));
+    assert_eq!(Rfc3164Record::parse("<13>Jan 32 00:00:00 host tag: msg").err(), error(ErrorKind::Timestamp, 8));
+    assert_eq!(Rfc3164Record::parse("<13>Jan  1 24:00:00 host tag: msg").err(), error(ErrorKind::Timestamp, 11));
+    assert_eq!(Rfc3164Record::parse("<13>Jan  1 00:00:00 host@1 tag: msg").err(), error(ErrorKind::Hostname, 20));
+    assert_eq!(Rfc3164Record::parse("<13>Jan  1 00:00:00 host my tag: msg").err(), error(ErrorKind::Tag, 25));
+    assert_eq!(Rfc3164Record::parse("<13>Jan  1 00:00:00 host tag[x1]: msg").err(), error(ErrorKind::Pid, 29));
+    assert_eq!(Rfc3164Record::parse("<13>Jan  1 00:00:00 host tag[1] msg").err(), error(ErrorKind::Separator, 31));
+    assert_eq!(Rfc3164Record::parse("<13>Jan  1 00:00:00 host tag").err(), error(ErrorKind::UnexpectedEnd, 28));
+
+    assert_eq!(Rfc5424Record::parse("<13>2 - - - - - -").err(), error(ErrorKind::Version, 4));
+    assert_eq!(Rfc5424Record::parse("<13>1 2024-13-01T00:00:00Z - - - - -").err(), error(ErrorKind::Timestamp, 6));
+    assert_eq!(Rfc5424Record::parse("<13>1 2024-12-01T00:00:00.1234567Z - - - - -").err(), error(ErrorKind::Timestamp, 26));
+    assert_eq!(Rfc5424Record::parse("<13>1 2024-12-01T00:00:00.12345678901Z - - - - -").err(), error(ErrorKind::Timestamp, 26));
+    assert_eq!(Rfc5424Record::parse("<13>1 2024-12-01T00:00:00 - - - - -").err(), error(ErrorKind::Timestamp, 25));
+    let proc_id = "1".repeat(129);
+    assert_eq!(Rfc5424Record::parse(&format!("<13>1 - - - {proc_id} - -")).err(), error(ErrorKind::Pid, 12));
+    assert_eq!(Rfc5424Record::parse("<13>1 - - - - bad\tid -").err(), error(ErrorKind::MsgId, 14));
+    assert_eq!(Rfc5424Record::parse("<13>1 - - - - - [id@x]").err(), error(ErrorKind::StructuredData, 17));
+    assert_eq!(Rfc5424Record::parse("<13>1 - - - - - [id key=value]").err(), error(ErrorKind::StructuredData, 24));
+    assert_eq!(Rfc5424Record::parse("<13>1 - - - - - [id key=\"value]").err(), error(ErrorKind::UnexpectedEnd, 31));
+    assert_eq!(Rfc5424Record::parse("<13>1 - - - - - [id]msg").err(), error(ErrorKind::Separator, 20));
+    assert_eq!(Rfc5424Record::parse("<13>1 - - - - -").err(), error(ErrorKind::UnexpectedEnd, 15));
+
+    let error = Rfc3164Record::parse("<13>Jan  1 00:00:00 host@1 tag: msg").expect_err("to fail");
+    assert_eq!(error.to_string(), "Invalid HOSTNAME at offset 20");
+}
+
+#[test]
+fn should_split_on_whitespace_with_markers_and_index() {
+    let collector = Collector::default();
+    let split = Split::new(SplitStrategy::Whitespace).with_marker("...").with_index(true);
+    let syslog = Syslog::new(Facility::LOG_USER, header::Hostname::new("in.memory").unwrap(), header::Tag::new("split").unwrap());
+    let mut logger = syslog.with_split(split).rfc3164(collector.clone()).with_buffer();
+
+    logger.write_str(Severity::LOG_INFO, "x").expect("Success");
+    let line = collector.pop().expect("to have line");
+    let header_size = line.len() - 1;
+
+    //Record that fits has no decorations
+    let text = "a ".repeat((1024 - header_size) / 2);
+    logger.write_str(Severity::LOG_INFO, &text).expect("Success");
+    assert_eq!(collector.pop().expect("to have line")[header_size..], text);
+    assert!(collector.pop().is_none());
+
+    let text = (0..600).map(|idx| format!("word{idx}")).collect::<Vec<_>>().join(" ");
+    logger.write_str(Severity::LOG_INFO, &text).expect("Success");
+    let lines = collector.0.borrow_mut().drain(..).collect::<Vec<_>>();
+    let total = lines.len();
+    assert!(total > 2, "total={}", total);
+
+    let mut parts = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        assert!(line.len() <= 1024, "line={}", line);
+        let prefix = format!("[{}/{}] ", idx + 1, total);
+        let mut part = line[header_size..].strip_prefix(prefix.as_str()).expect("to have index");
+        if idx > 0 {
+            part = part.strip_prefix("...").expect("to have leading marker");
+        }
+        if idx + 1 < total {
+            part = part.strip_suffix("...").expect("to have trailing marker");
+        }
+        assert!(part.starts_with("word") && !part.ends_with(' '), "part={}", part);
+        parts.push(part);
+    }
+    assert_eq!(parts.join(" "), text);
+}
+
+#[test]
+fn should_split_on_newline_and_utf8_boundary() {
+    let collector = Collector::default();
+    let syslog = Syslog::new(Facility::LOG_USER, header::Hostname::new("in.memory").unwrap(), header::Tag::new("split").unwrap());
+    let mut logger = syslog.with_split(Split::new(SplitStrategy::Newline)).rfc5424(collector.clone()).with_buffer();
+
+    logger.write_str(Severity::LOG_INFO, None, "x").expect("Success");
+    let line = collector.pop().expect("to have line");
+    let header_size = line.len() - 1;
+
+    let text = (0..300).map(|idx| format!("line {idx}")).collect::<Vec<_>>().join("\n");
+    logger.write_str(Severity::LOG_INFO, None, &text).expect("Success");
+    let lines = collector.0.borrow_mut().drain(..).collect::<Vec<_>>();
+    assert!(lines.len() > 1);
+    let parts = lines.iter().map(|line| &line[header_size..]).collect::<Vec<_>>();
+    for part in parts.iter() {
+        assert!(part.starts_with("line ") && !part.contains(" \n"), "part={}", part);
+    }
+    assert_eq!(parts.join("\n"), text);
+
+    //Without any separator text is split at last character that fits
+    let text = "日本".repeat(700);
+    logger.write_str(Severity::LOG_INFO, None, &text).expect("Success");
+    let lines = collector.0.borrow_mut().drain(..).collect::<Vec<_>>();
+    assert!(lines.len() > 1);
+    for line in lines[..lines.len() - 1].iter() {
+        assert!(line.len() <= 2048 && line.len() > 2045, "len={}", line.len());
+    }
+    assert_eq!(lines.iter().map(|line| &line[header_size..]).collect::<String>(), text);
+}
+
+#[test]
+fn should_index_streamed_record_without_total() {
+    use core::fmt::Write;
+
+    let collector = Collector::default();
+    let split = Split::new(SplitStrategy::Hard).with_index(true);
+    let syslog = Syslog::new(Facility::LOG_USER, header::Hostname::new("in.memory").unwrap(), header::Tag::new("split").unwrap());
+    let mut logger = syslog.with_split(split).rfc3164(collector.clone()).with_buffer();
+
+    let mut record = logger.write_record(Severity::LOG_INFO);
+    for _ in 0..300 {
+        write!(record, "{:>10}", 1234567890).expect("Success");
+    }
+    record.flush().expect("Success");
+    drop(record);
+
+    let lines = collector.0.borrow_mut().drain(..).collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4);
+    let mut text = String::new();
+    for (idx, line) in lines.iter().enumerate() {
+        assert!(line.len() <= 1024);
+        let prefix = format!("[{}] ", idx + 1);
+        let start = line.find(&prefix).expect("to have index");
+        text.push_str(&line[start + prefix.len()..]);
+    }
+    assert_eq!(text, "1234567890".repeat(300));
+
+    //Total is known when whole text is written at once
+    logger.write_str(Severity::LOG_INFO, &text).expect("Success");
+    let lines = collector.0.borrow_mut().drain(..).collect::<Vec<_>>();
+    let total = lines.len();
+    assert_eq!(total, 4);
+    let mut written = String::new();
+    for (idx, line) in lines.iter().enumerate() {
+        assert!(line.len() <= 1024);
+        let prefix = format!("[{}/{}] ", idx + 1, total);
+        let start = line.find(&prefix).expect("to have index");
+        written.push_str(&line[start + prefix.len()..]);
+    }
+    assert_eq!(written, text);
+}
+
+#[test]
+fn should_limit_record_size_and_truncate() {
+    const SIZE: usize = syslog_client::str_buf::capacity(8192);
+
+    let collector = Collector::default();
+    let syslog = Syslog::new(Facility::LOG_USER, header::Hostname::new("in.memory").unwrap(), header::Tag::new("size").unwrap());
+    let mut logger = Rfc3164BufferedLogger::<_, SIZE>::new(syslog.rfc3164(collector.clone()));
+
+    logger.write_str(Severity::LOG_INFO, "x").expect("Success");
+    let line = collector.pop().expect("to have line");
+    let header_size = line.len() - 1;
+
+    //Larger buffer fits whole record
+    let text = "1".repeat(8192 - header_size);
+    logger.write_str(Severity::LOG_INFO, &text).expect("Success");
+    assert_eq!(collector.pop().expect("to have line")[header_size..], text);
+    assert!(collector.pop().is_none());
+
+    //Max size is lower than buffer capacity
+    let syslog = Syslog::new(Facility::LOG_USER, header::Hostname::new("in.memory").unwrap(), header::Tag::new("size").unwrap());
+    let mut logger = Rfc3164BufferedLogger::<_, SIZE>::new(syslog.with_max_size(480).rfc3164(collector.clone()));
+    let text = "2".repeat(1000);
+    logger.write_str(Severity::LOG_INFO, &text).expect("Success");
+    let lines = collector.0.borrow_mut().drain(..).collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0].len(), 480);
+    assert_eq!(lines[1].len(), 480);
+    assert_eq!(lines.iter().map(|line| &line[header_size..]).collect::<String>(), text);
+
+    //Size below header still makes progress
+    let syslog = Syslog::new(Facility::LOG_USER, header::Hostname::new("in.memory").unwrap(), header::Tag::new("size").unwrap());
+    let mut logger = syslog.with_max_size(1).rfc3164(collector.clone()).with_buffer();
+    logger.write_str(Severity::LOG_INFO, "abcñdef").expect("Success");
+    let lines = collector.0.borrow_mut().drain(..).collect::<Vec<_>>();
+    assert_eq!(lines.iter().map(|line| &line[header_size..]).collect::<Vec<_>>(), ["abc", "ñde", "f"]);
+
+    //Truncation sends single record with marker
+    let split = Split::new(SplitStrategy::Whitespace).with_marker("...").with_index(true).with_truncation(true);
+    let syslog = Syslog::new(Facility::LOG_USER, header::Hostname::new("in.memory").unwrap(), header::Tag::new("size").unwrap());
+    let mut logger = syslog.with_max_size(256).with_split(split).rfc3164(collector.clone()).with_buffer();
+    let text = "word ".repeat(100);
+    logger.write_str(Severity::LOG_INFO, &text).expect("Success");
+    let line = collector.pop().expect("to have line");
+    assert!(collector.pop().is_none());
+    assert!(line.len() <= 256);
+    let msg = &line[header_size..];
+    assert!(msg.starts_with("word word") && msg.ends_with("word..."), "msg={}", msg);
+
+    let mut record = logger.write_record(Severity::LOG_INFO);
+    for _ in 0..100 {
+        record.write_str("word ").expect("Success");
+    }
+    record.flush().expect("Success");
+    assert_eq!(collector.pop().expect("to have line")[header_size..], *msg);
+    assert!(collector.pop().is_none());
+
+    //Record is started anew after flush
+    record.write_str("short").expect("Success");
+    record.flush().expect("Success");
+    assert_eq!(collector.pop().expect("to have line")[header_size..], *"short");
+}
+
+#[test]
+fn should_sanitize_control_characters() {
+    let collector = Collector::default();
+    let syslog = || Syslog::new(Facility::LOG_USER, header::Hostname::new("in.memory").unwrap(), header::Tag::new("sanitize").unwrap());
+
+    let mut logger = syslog().rfc3164(collector.clone()).with_buffer();
+    logger.write_str(Severity::LOG_INFO, "x").expect("Success");
+    let header_size = collector.pop().expect("to have line").len() - 1;
+    logger.write_str(Severity::LOG_INFO, "fake\n<13>line\x7f").expect("Success");
+    assert_eq!(collector.pop().expect("to have line")[header_size..], *"fake\n<13>line\x7f");
+
+    let mut logger = syslog().with_sanitize(SanitizePolicy::Escape).rfc3164(collector.clone()).with_buffer();
+    logger.write_str(Severity::LOG_INFO, "fake\n<13>line\x7f\ttab").expect("Success");
+    assert_eq!(collector.pop().expect("to have line")[header_size..], *"fake#012<13>line#177#011tab");
+
+    let mut logger = syslog().with_sanitize(SanitizePolicy::Space).rfc3164(collector.clone()).with_buffer();
+    logger.write_str(Severity::LOG_INFO, "fake\r\nline").expect("Success");
+    assert_eq!(collector.pop().expect("to have line")[header_size..], *"fake  line");
+
+    //Escape sequence is never split between chunks
+    let split = Split::new(SplitStrategy::Hard).with_index(true);
+    let mut logger = syslog().with_sanitize(SanitizePolicy::Escape).with_split(split).rfc3164(collector.clone()).with_buffer();
+    //Every alignment of escape sequence against chunk's end
+    for prefix_len in 0..4 {
+        let prefix = "x".repeat(prefix_len);
+        let text = format!("{prefix}{}", "\n".repeat(1000));
+        logger.write_str(Severity::LOG_INFO, &text).expect("Success");
+        let lines = collector.0.borrow_mut().drain(..).collect::<Vec<_>>();
+        let total = lines.len();
+        assert!(total > 3, "total={}", total);
+        let mut msg = String::new();
+        for (idx, line) in lines.iter().enumerate() {
+            assert!(line.len() <= 1024);
+            let part = line[header_size..].strip_prefix(format!("[{}/{}] ", idx + 1, total).as_str()).expect("to have index");
+            let escapes = part.trim_start_matches('x');
+            assert!(escapes.len() % 4 == 0 && !part.contains('\n'), "part={}", part);
+            msg.push_str(part);
+        }
+        assert_eq!(msg, format!("{prefix}{}", "#012".repeat(1000)));
+    }
+
+    assert_eq!(format!("{}", SanitizePolicy::Escape.display("a\x00b")), "a#000b");
+    assert_eq!(format!("{}", SanitizePolicy::PassThrough.display("a\x00b")), "a\x00b");
+}
+
+#[derive(Clone, Default)]
+struct Broken(Rc<core::cell::Cell<usize>>);
+
+impl Transport<Closed> for Broken {
+    fn write(&mut self, _severity: Severity, _msg: &str) -> Result<(), Closed> {
+        self.0.set(self.0.get() + 1);
+        Err(Closed)
+    }
+}
+
+impl MakeTransport for Broken {
+    type Error = Closed;
+    type Transport = Self;
+
+    fn create(&self) -> Result<Self::Transport, Self::Error> {
+        Ok(self.clone())
+    }
+}
+
+#[test]
+fn should_stop_retrying_terminal_write_error() {
+    let syslog = || Syslog::new(Facility::LOG_USER, header::Hostname::new("broken").unwrap(), header::Tag::new("broken").unwrap());
+    for retry_count in [0, 2] {
+        let transport = Broken::default();
+        let mut logger = syslog().with_retry_count(retry_count).rfc3164(transport.clone()).with_buffer();
+        assert!(logger.write_str(Severity::LOG_ERR, "lost").is_err());
+        assert_eq!(transport.0.get(), usize::from(retry_count) + 1);
+    }
 }
@@ -0,0 +1,171 @@
+use core::time;
+
+use syslog_client::syslog::header::{Hostname, Tag};
+use syslog_client::testing::{FixedClock, SyslogServer};
+use syslog_client::writer::transport::{self, Framing};
+use syslog_client::{Facility, Severity, Syslog};
+
+const TAG: Tag = match Tag::new("testing") {
+    Some(tag) => tag,
+    None => panic!("not valid tag"),
+};
+const HOSTNAME: Hostname = match Hostname::new("in.test") {
+    Some(hostname) => hostname,
+    None => panic!("not valid hostname"),
+};
+
+//2024-12-31T23:59:59Z
+const TIMESTAMP: u64 = 1_735_689_599;
+
+fn syslog() -> Syslog {
+    Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP))
+}
+
+#[test]
+fn should_receive_udp_records() {
+    let server = SyslogServer::udp().expect("to start server");
+    let transport = transport::Udp {
+        local_port: 0,
+        remote_addr: server.addr().expect("to have address"),
+    };
+    let mut logger = syslog().rfc3164(transport).with_buffer();
+    logger.write_str(Severity::LOG_WARNING, "my warning").expect("Success");
+    logger.write_str(Severity::LOG_ERR, "my error").expect("Success");
+
+    let records = server.expect_records(2);
+    assert!(!records[0].is_rfc5424());
+    assert_eq!(records[0].facility(), Some(Facility::LOG_USER));
+    assert_eq!(records[0].severity(), Some(Severity::LOG_WARNING));
+    assert_eq!(records[0].msg(), Some("my warning"));
+
+    let error = server.find_by_severity(Severity::LOG_ERR).expect("to have error");
+    let record = error.rfc3164().expect("to parse record");
+    assert_eq!(record.tag.as_str(), "testing");
+    assert_eq!(record.pid, Some(std::process::id()));
+    assert!(server.find(|record| record.severity() == Some(Severity::LOG_DEBUG)).is_none());
+
+    server.clear();
+    assert!(server.records().is_empty());
+}
+
+#[test]
+fn should_receive_tcp_records_with_both_framings() {
+    for framing in [Framing::NonTransparent, Framing::OctetCounting] {
+        let server = SyslogServer::tcp(framing).expect("to start server");
+        let transport = transport::Tcp {
+            remote_addr: server.addr().expect("to have address"),
+            timeout: Some(time::Duration::from_secs(5)),
+        }.with_framing(framing);
+        let mut logger = syslog().rfc5424(transport).with_buffer();
+        logger.write_str(Severity::LOG_INFO, None, "first").expect("Success");
+        logger.write_str(Severity::LOG_INFO, None, "multi\nline").expect("Success");
+        drop(logger);
+
+        let expected: &[&str] = match framing {
+            //Embedded LF splits record
+            Framing::NonTransparent => &["first", "multi", "line"],
+            Framing::OctetCounting => &["first", "multi\nline"],
+        };
+        let records = server.expect_records(expected.len());
+        assert!(records[0].is_rfc5424());
+        let record = records[0].rfc5424().expect("to parse record");
+        assert_eq!(record.hostname.expect("to have hostname").as_str(), "in.test");
+        assert_eq!(record.timestamp.expect("to have timestamp").year, 2024);
+        let messages = records.iter().map(|record| record.msg().unwrap_or(record.as_str())).collect::<Vec<_>>();
+        assert_eq!(messages, expected);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn should_receive_unix_records() {
+    let path = std::env::temp_dir().join(format!("syslog-client-testing-{}", std::process::id()));
+    let server = SyslogServer::unix(&path).expect("to start server");
+    let path = server.path().expect("to have path").to_str().expect("utf-8 path").to_owned();
+    let mut logger = syslog().rfc3164(transport::Unix::new(&path)).with_buffer();
+    logger.write_str(Severity::LOG_NOTICE, "my notice").expect("Success");
+
+    let records = server.expect_records(1);
+    assert_eq!(records[0].severity(), Some(Severity::LOG_NOTICE));
+    assert_eq!(records[0].msg(), Some("my notice"));
+    drop(server);
+    assert!(!std::path::Path::new(&path).exists());
+}
+
+#[test]
+fn should_retry_create_and_write_as_scripted() {
+    use syslog_client::testing::{Fault, Mock, MockError, Operation};
+
+    //Transient create failures are retried
+    let mock = Mock::new().with_create_fault(1..=2, Fault::Transient);
+    let mut logger = syslog().with_retry_count(2).rfc3164(mock.clone()).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "first").expect("Success");
+    assert_eq!((mock.creates(), mock.writes()), (3, 1));
+    //Transport is cached once it writes
+    logger.write_str(Severity::LOG_ERR, "second").expect("Success");
+    assert_eq!((mock.creates(), mock.writes()), (3, 2));
+    assert_eq!(mock.records().len(), 2);
+
+    //Terminal create failure gives up immediately
+    let mock = Mock::new().with_create_fault(1..=1, Fault::Terminal);
+    let mut logger = syslog().with_retry_count(2).rfc3164(mock.clone()).with_buffer();
+    let error = logger.write_str(Severity::LOG_ERR, "lost").expect_err("to fail");
+    assert_eq!(error, MockError {
+        operation: Operation::Create,
+        attempt: 1,
+        fault: Fault::Terminal,
+    });
+    assert_eq!((mock.creates(), mock.writes()), (1, 0));
+
+    //Transient write failure retries the same transport
+    let mock = Mock::new().with_write_fault(1..=1, Fault::Transient);
+    let mut logger = syslog().with_retry_count(2).rfc3164(mock.clone()).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "first").expect("Success");
+    assert_eq!((mock.creates(), mock.writes()), (1, 2));
+
+    //Terminal write failure re-creates transport
+    let mock = Mock::new().with_write_fault(1..=1, Fault::Terminal);
+    let mut logger = syslog().with_retry_count(2).rfc3164(mock.clone()).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "first").expect("Success");
+    assert_eq!((mock.creates(), mock.writes()), (2, 2));
+
+    //Transport which failed last attempt is not cached
+    let mock = Mock::new().with_write_fault(1..=3, Fault::Transient);
+    let mut logger = syslog().with_retry_count(2).rfc3164(mock.clone()).with_buffer();
+    let error = logger.write_str(Severity::LOG_ERR, "lost").expect_err("to fail");
+    assert_eq!((error.operation, error.attempt), (Operation::Write, 3));
+    assert_eq!((mock.creates(), mock.writes()), (1, 3));
+    logger.write_str(Severity::LOG_ERR, "second").expect("Success");
+    assert_eq!((mock.creates(), mock.writes()), (2, 4));
+    assert_eq!(mock.records().len(), 1);
+    assert!(mock.records()[0].1.ends_with(" second"));
+
+    //Terminal write failure on every attempt gives up after retries
+    let mock = Mock::new().with_write_fault(.., Fault::Terminal);
+    let mut logger = syslog().with_retry_count(2).rfc3164(mock.clone()).with_buffer();
+    logger.write_str(Severity::LOG_ERR, "lost").expect_err("to fail");
+    assert_eq!((mock.creates(), mock.writes()), (3, 3));
+}
+
+#[test]
+fn should_resend_split_chunk_after_write_failure() {
+    use syslog_client::split::{Split, SplitStrategy};
+    use syslog_client::testing::{Fault, Mock};
+
+    let split = Split::new(SplitStrategy::Whitespace).with_marker("...").with_index(true);
+    let mock = Mock::new().with_write_fault(1..=1, Fault::Transient);
+    let mut logger = syslog().with_retry_count(0).with_split(split).rfc3164(mock.clone()).with_buffer();
+    let text = (0..300).map(|idx| format!("word{idx}")).collect::<Vec<_>>().join(" ");
+
+    let mut record = logger.write_record(Severity::LOG_INFO);
+    assert!(record.write_str(&text).is_err());
+    assert!(mock.records().is_empty());
+    //Failed chunk is restored, so the rest of buffered text is sent as it is
+    record.flush().expect("Success");
+    drop(record);
+
+    let records = mock.records();
+    assert_eq!(records.len(), 1);
+    let msg = records[0].1.split_once(": ").expect("to have message").1;
+    assert!(msg.len() > 900 && text.starts_with(msg), "msg={}", msg);
+}
@@ -0,0 +1,164 @@
+use core::time;
+use std::convert::TryFrom;
+use std::io::Read;
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::thread;
+
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use rustls::{RootCertStore, ServerConfig, ServerConnection, StreamOwned};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::server::WebPkiClientVerifier;
+
+use syslog_client::syslog::header::{Hostname, Tag};
+use syslog_client::testing::FixedClock;
+use syslog_client::writer::{MakeTransport, TransportError};
+use syslog_client::writer::transport::{Tls, TlsError, LOCAL_HOST};
+use syslog_client::{Facility, Severity, Syslog};
+
+const TAG: Tag = match Tag::new("tls") {
+    Some(tag) => tag,
+    None => panic!("not valid tag"),
+};
+const HOSTNAME: Hostname = match Hostname::new("in.tls") {
+    Some(hostname) => hostname,
+    None => panic!("not valid hostname"),
+};
+const SERVER_NAME: &str = "syslog.test";
+//2024-12-31T23:59:59Z
+const TIMESTAMP: u64 = 1_735_689_599;
+
+struct Authority {
+    cert: rcgen::Certificate,
+    key: KeyPair,
+}
+
+impl Authority {
+    fn new() -> Self {
+        let mut params = CertificateParams::new(Vec::<String>::new()).expect("valid params");
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let key = KeyPair::generate().expect("generate key");
+        let cert = params.self_signed(&key).expect("self sign CA");
+        Self {
+            cert,
+            key,
+        }
+    }
+
+    fn roots(&self) -> RootCertStore {
+        let mut roots = RootCertStore::empty();
+        roots.add(self.cert.der().clone()).expect("add CA");
+        roots
+    }
+
+    fn issue(&self, name: &str) -> (Vec<CertificateDer<'static>>, PrivateKeyDer<'static>) {
+        let params = CertificateParams::new(vec![name.to_owned()]).expect("valid params");
+        let key = KeyPair::generate().expect("generate key");
+        let cert = params.signed_by(&key, &self.cert, &self.key).expect("sign certificate");
+        (vec![cert.der().clone()], PrivateKeyDer::Pkcs8(key.serialize_der().into()))
+    }
+}
+
+fn provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+///Starts server accepting single connection, returning everything received
+fn start_server(config: ServerConfig) -> (std::net::SocketAddr, thread::JoinHandle<std::io::Result<String>>) {
+    let listener = TcpListener::bind((LOCAL_HOST, 0)).expect("to bind server");
+    let addr = listener.local_addr().expect("to have address");
+    let config = Arc::new(config);
+    let handle = thread::spawn(move || {
+        let (socket, _) = listener.accept()?;
+        socket.set_read_timeout(Some(time::Duration::from_secs(5)))?;
+        let connection = ServerConnection::new(config).map_err(std::io::Error::other)?;
+        let mut stream = StreamOwned::new(connection, socket);
+        let mut received = String::new();
+        stream.read_to_string(&mut received)?;
+        Ok(received)
+    });
+    (addr, handle)
+}
+
+#[test]
+fn should_send_octet_counted_messages_over_tls() {
+    let authority = Authority::new();
+    let (cert_chain, key) = authority.issue(SERVER_NAME);
+    let config = ServerConfig::builder_with_provider(provider()).with_safe_default_protocol_versions().expect("valid versions")
+                                                                .with_no_client_auth()
+                                                                .with_single_cert(cert_chain, key).expect("valid certificate");
+    let (addr, server) = start_server(config);
+
+    let tls = Tls::new(addr, authority.roots()).expect("valid config")
+                                               .with_server_name(ServerName::try_from(SERVER_NAME).expect("valid name"))
+                                               .with_timeout(time::Duration::from_secs(5));
+    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc5424(tls).with_buffer();
+    logger.write_str(Severity::LOG_ERR, None, "my tls error").expect("Success");
+    logger.write_str(Severity::LOG_ERR, None, "multi\nline").expect("Success");
+    drop(logger);
+
+    let pid = std::process::id();
+    let line1 = format!("<11>1 2024-12-31T23:59:59Z in.tls tls {pid} - - my tls error");
+    let line2 = format!("<11>1 2024-12-31T23:59:59Z in.tls tls {pid} - - multi\nline");
+    let received = server.join().expect("server to finish").expect("server to receive");
+    assert_eq!(received, format!("{} {line1}{} {line2}", line1.len(), line2.len()));
+}
+
+#[test]
+fn should_authenticate_client_over_tls() {
+    let authority = Authority::new();
+    let (cert_chain, key) = authority.issue(SERVER_NAME);
+    let client_verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(authority.roots()), provider()).build().expect("valid verifier");
+    let config = ServerConfig::builder_with_provider(provider()).with_safe_default_protocol_versions().expect("valid versions")
+                                                                .with_client_cert_verifier(client_verifier)
+                                                                .with_single_cert(cert_chain, key).expect("valid certificate");
+    let (addr, server) = start_server(config);
+
+    let (client_cert_chain, client_key) = authority.issue("client.test");
+    let tls = Tls::with_client_auth(addr, authority.roots(), client_cert_chain, client_key).expect("valid config")
+                                                                                           .with_server_name(ServerName::try_from(SERVER_NAME).expect("valid name"))
+                                                                                           .with_timeout(time::Duration::from_secs(5));
+    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc5424(tls).with_buffer();
+    logger.write_str(Severity::LOG_ERR, None, "my mtls error").expect("Success");
+    drop(logger);
+
+    let pid = std::process::id();
+    let line = format!("<11>1 2024-12-31T23:59:59Z in.tls tls {pid} - - my mtls error");
+    let received = server.join().expect("server to finish").expect("server to receive");
+    assert_eq!(received, format!("{} {line}", line.len()));
+}
+
+#[test]
+fn should_fail_tls_handshake_with_terminal_error() {
+    let authority = Authority::new();
+    let (cert_chain, key) = authority.issue(SERVER_NAME);
+    let config = ServerConfig::builder_with_provider(provider()).with_safe_default_protocol_versions().expect("valid versions")
+                                                                .with_no_client_auth()
+                                                                .with_single_cert(cert_chain, key).expect("valid certificate");
+
+    //Server name doesn't match certificate
+    let (addr, server) = start_server(config.clone());
+    let tls = Tls::new(addr, authority.roots()).expect("valid config").with_timeout(time::Duration::from_secs(5));
+    match tls.create() {
+        Ok(_) => panic!("Handshake should fail"),
+        Err(error) => {
+            assert!(matches!(error, TlsError::Tls(rustls::Error::InvalidCertificate(_))), "Unexpected error: {}", error);
+            assert!(error.is_terminal());
+        }
+    }
+    let _ = server.join();
+
+    //Unknown certificate authority
+    let (addr, server) = start_server(config);
+    let tls = Tls::new(addr, Authority::new().roots()).expect("valid config")
+                                                      .with_server_name(ServerName::try_from(SERVER_NAME).expect("valid name"))
+                                                      .with_timeout(time::Duration::from_secs(5));
+    match tls.create() {
+        Ok(_) => panic!("Handshake should fail"),
+        Err(error) => {
+            assert!(matches!(error, TlsError::Tls(rustls::Error::InvalidCertificate(_))), "Unexpected error: {}", error);
+            assert!(error.is_terminal());
+        }
+    }
+    let _ = server.join();
+}
@@ -0,0 +1,108 @@
+use core::time;
+
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, UdpSocket};
+
+use syslog_client::syslog::header::{Hostname, Tag};
+use syslog_client::testing::FixedClock;
+use syslog_client::writer::transport;
+use syslog_client::{Facility, Severity, Syslog};
+
+const TAG: Tag = match Tag::new("tokio") {
+    Some(tag) => tag,
+    None => panic!("not valid tag"),
+};
+const HOSTNAME: Hostname = match Hostname::new("in.tokio") {
+    Some(hostname) => hostname,
+    None => panic!("not valid hostname"),
+};
+
+//2024-12-31T23:59:59Z
+const TIMESTAMP: u64 = 1_735_689_599;
+
+#[tokio::test]
+async fn should_write_rfc3164_messages_tcp() {
+    let pid = std::process::id();
+    let header = format!("<11>Dec 31 23:59:59 in.tokio tokio[{pid}]:");
+    for framing in [transport::Framing::NonTransparent, transport::Framing::OctetCounting] {
+        let server = TcpListener::bind((transport::LOCAL_HOST, 0)).await.expect("to bind server");
+        let tcp = transport::Tcp {
+            remote_addr: server.local_addr().expect("to have address"),
+            timeout: Some(time::Duration::from_secs(5)),
+        }.with_framing(framing);
+
+        let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc3164_async(tcp);
+        logger.write_str(Severity::LOG_ERR, "my tcp error").await.expect("Success");
+        logger.write_str(Severity::LOG_ERR, "multi\nline").await.expect("Success");
+        //Connection is closed on drop
+        drop(logger);
+
+        let (mut client, _) = server.accept().await.expect("to accept client");
+        let mut received = String::new();
+        client.read_to_string(&mut received).await.expect("to read");
+
+        let line1 = format!("{header} my tcp error");
+        let line2 = format!("{header} multi\nline");
+        let expected = match framing {
+            transport::Framing::NonTransparent => format!("{line1}\n{line2}\n"),
+            transport::Framing::OctetCounting => format!("{} {line1}{} {line2}", line1.len(), line2.len()),
+        };
+        assert_eq!(received, expected);
+    }
+}
+
+#[tokio::test]
+async fn should_write_rfc5424_messages_udp() {
+    use syslog_client::syslog::header::MsgId;
+
+    const MSG_ID: MsgId = match MsgId::new("audit") {
+        Some(msg_id) => msg_id,
+        None => panic!("not valid msg id"),
+    };
+
+    let server = UdpSocket::bind((transport::LOCAL_HOST, 0)).await.expect("to bind server");
+    let udp = transport::Udp {
+        local_port: 0,
+        remote_addr: server.local_addr().expect("to have address"),
+    };
+
+    let pid = std::process::id();
+    let header = format!("<11>1 2024-12-31T23:59:59Z in.tokio tokio {pid} audit - ");
+    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).with_clock(&FixedClock(TIMESTAMP)).rfc5424_async(udp);
+    logger.write_str(Severity::LOG_ERR, Some(&MSG_ID), "my udp error").await.expect("Success");
+
+    let mut datagram = [0u8; 2048];
+    let size = server.recv(&mut datagram).await.expect("to receive");
+    assert_eq!(core::str::from_utf8(&datagram[..size]).expect("utf-8"), format!("{header}my udp error"));
+
+    //check split behavior
+    let chunk1_size = 2048 - header.len();
+    let mut message = "1".repeat(chunk1_size);
+    message.push('0');
+    logger.write_str(Severity::LOG_ERR, Some(&MSG_ID), &message).await.expect("Success");
+
+    let size = server.recv(&mut datagram).await.expect("to receive");
+    assert_eq!(core::str::from_utf8(&datagram[..size]).expect("utf-8"), format!("{header}{}", &message[..chunk1_size]));
+    let size = server.recv(&mut datagram).await.expect("to receive");
+    assert_eq!(core::str::from_utf8(&datagram[..size]).expect("utf-8"), format!("{header}0"));
+}
+
+#[tokio::test]
+async fn should_retry_tcp_connection() {
+    let server = TcpListener::bind((transport::LOCAL_HOST, 0)).await.expect("to bind server");
+    let remote_addr = server.local_addr().expect("to have address");
+    //Nobody listens
+    drop(server);
+
+    let tcp = transport::Tcp {
+        remote_addr,
+        timeout: Some(time::Duration::from_secs(5)),
+    }.with_framing(transport::Framing::OctetCounting);
+    let mut logger = Syslog::new(Facility::LOG_USER, HOSTNAME, TAG).rfc3164_async(tcp);
+    //Logger must be usable within spawned task
+    let task = tokio::spawn(async move {
+        logger.write_str(Severity::LOG_ERR, "my tcp error").await
+    });
+    let error = task.await.expect("task to complete").expect_err("Should fail");
+    assert_eq!(error.kind(), std::io::ErrorKind::ConnectionRefused);
+}